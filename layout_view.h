@@ -0,0 +1,127 @@
+/*
+ * layout-view C interface.
+ *
+ * All functions returning `char *` return a NUL-terminated UTF-8 JSON string
+ * owned by the library; release it with free_c_string(). On failure they
+ * return NULL and record an error for the calling thread, available through
+ * last_error_code_c() and last_error_message_c().
+ *
+ * ABI stability: LAYOUT_VIEW_ABI_VERSION is bumped whenever an exported
+ * function is removed or changes its signature, ownership rules or error
+ * codes. Adding functions or adding fields to the JSON output does not bump
+ * it. Check at load time that layout_view_abi_version() equals the
+ * LAYOUT_VIEW_ABI_VERSION this header was compiled against.
+ */
+
+#ifndef LAYOUT_VIEW_H
+#define LAYOUT_VIEW_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit; regenerate with `UPDATE_C_HEADER=1 cargo test c_header`. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * C ABI 版本号：删除或重命名导出函数、修改参数或返回值、修改字符串所有权约定、
+ * 修改已有错误码含义时加 1；新增函数、新增错误码、JSON 结果新增字段不改变版本号
+ */
+#define LAYOUT_VIEW_ABI_VERSION 1
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * C function to get the library version, e.g. "0.1.0"
+ * The returned string is static and must not be freed
+ */
+const char *layout_view_version(void);
+
+/**
+ * C function to get the ABI version of the loaded library
+ * Callers should check it equals the LAYOUT_VIEW_ABI_VERSION they were compiled against
+ */
+uint32_t layout_view_abi_version(void);
+
+/**
+ * C function to classify Excel sheets and return results as JSON string
+ * The caller is responsible for freeing the returned string using free_c_string
+ *
+ * # Safety
+ * `xlsx_path` must be null or point to a valid NUL-terminated string.
+ */
+char *classify_excel_sheets_c(const char *xlsx_path);
+
+/**
+ * C function to classify an in-memory workbook and return results as JSON string
+ * The caller is responsible for freeing the returned string using free_c_string
+ *
+ * # Safety
+ * `data` must be null or point to at least `len` readable bytes.
+ */
+char *classify_excel_sheets_bytes_c(const uint8_t *data, size_t len);
+
+/**
+ * C function to classify Excel sheets with classifier options given as a JSON string
+ * `options_json` may be null to use the default thresholds; fields left out of the
+ * JSON object keep their default values. Returns null on invalid options.
+ * The caller is responsible for freeing the returned string using free_c_string
+ *
+ * # Safety
+ * `xlsx_path` must be null or point to a valid NUL-terminated string, and
+ * `options_json` must be null or point to a valid NUL-terminated string.
+ */
+char *classify_excel_sheets_with_options_c(const char *xlsx_path, const char *options_json);
+
+/**
+ * C function to classify an in-memory workbook with classifier options given as a JSON string
+ * The caller is responsible for freeing the returned string using free_c_string
+ *
+ * # Safety
+ * `data` must be null or point to at least `len` readable bytes, and
+ * `options_json` must be null or point to a valid NUL-terminated string.
+ */
+char *classify_excel_sheets_bytes_with_options_c(const uint8_t *data,
+                                                 size_t len,
+                                                 const char *options_json);
+
+/**
+ * C function to extract key-value fields from Form sheets and return them as JSON string
+ * The caller is responsible for freeing the returned string using free_c_string
+ *
+ * # Safety
+ * `xlsx_path` must be null or point to a valid NUL-terminated string.
+ */
+char *extract_form_fields_c(const char *xlsx_path);
+
+/**
+ * C function to get the error code of the last failed call on the current thread
+ * Returns 0 if the last call succeeded. Codes: 1 invalid argument, 2 I/O error,
+ * 3 unsupported format, 4 invalid workbook, 5 invalid config, 6 serialization error,
+ * 7 internal error (panic)
+ */
+int32_t last_error_code_c(void);
+
+/**
+ * C function to get the error message of the last failed call on the current thread
+ * Returns null if the last call succeeded.
+ * The caller is responsible for freeing the returned string using free_c_string
+ */
+char *last_error_message_c(void);
+
+/**
+ * C function to free strings allocated by Rust
+ *
+ * # Safety
+ * `ptr` must be null or a pointer previously returned by this library,
+ * and must not be freed twice.
+ */
+void free_c_string(char *ptr);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LAYOUT_VIEW_H */
@@ -0,0 +1,94 @@
+"""layout-view Python 扩展模块的类型声明"""
+
+import os
+from typing import Any, Dict, Iterator, List, Mapping, Optional, Union
+
+Source = Union[str, "os.PathLike[str]", bytes, bytearray]
+Config = Union[Mapping[str, Any], str, None]
+
+class LayoutViewError(Exception):
+    """无法识别、损坏或分析失败的工作簿"""
+
+class ColumnDataTypeInfo:
+    column_index: int
+    numeric_count: int
+    text_count: int
+    date_count: int
+    datetime_count: int
+    time_count: int
+    duration_count: int
+    bool_count: int
+    error_count: int
+    total_count: int
+    numeric_type_ratio: float
+    date_type_ratio: float
+    datetime_type_ratio: float
+    time_type_ratio: float
+    duration_type_ratio: float
+    bool_type_ratio: float
+    error_type_ratio: float
+    error_kinds: Dict[str, int]
+    semantic_type: str
+    semantic_ratio: float
+    semantic_matches: Dict[str, float]
+    def to_dict(self) -> Dict[str, Any]: ...
+
+class _SheetFields:
+    """SheetDensity 和 ClassifiedSheet 共有的字段"""
+
+    sheet_name: str
+    first_row: int
+    first_col: int
+    end_row: int
+    end_col: int
+    total_cells: int
+    data_cells: int
+    density: float
+    visible: str
+    first_row_first_col_content: Optional[str]
+    last_row_first_col_content: Optional[str]
+    data_type_mix: float
+    column_data_types: List[ColumnDataTypeInfo]
+    error_ratio: float
+    header_row: Optional[int]
+    column_names: List[str]
+    header_rows: List[int]
+    header_tree: List[Dict[str, Any]]
+    row_type_consistency: float
+    aspect_ratio: float
+    sampling_strategy: str
+    sampled_rows: int
+    merged_cells: int
+    merged_cell_ratio: float
+    cell_density: float
+    shape_features: Dict[str, Any]
+    crosstab: Optional[Dict[str, Any]]
+    merged_regions: List[Dict[str, int]]
+    regions: List[Dict[str, Any]]
+    def to_dict(self) -> Dict[str, Any]: ...
+    def to_json(self) -> str: ...
+
+class SheetDensity(_SheetFields): ...
+
+class RecordIterator(Iterator[Dict[str, Any]]):
+    """逐条产生 `{"sheet_name", "row", "record"}` 的记录迭代器"""
+
+    def __iter__(self) -> "RecordIterator": ...
+    def __next__(self) -> Dict[str, Any]: ...
+
+class ClassifiedSheet(_SheetFields):
+    sheet_type: str
+    layout_type: Optional[str]
+    classification_reason: str
+    confidence: float
+    explanation: Dict[str, Any]
+
+def classify_excel_sheets(source: Source, config: Config = None) -> List[ClassifiedSheet]: ...
+def calculate_sheet_density(source: Source, config: Config = None) -> List[SheetDensity]: ...
+def extract_form_fields(source: Source, config: Config = None) -> List[Dict[str, Any]]: ...
+def extract_records(
+    source: Source, sheet: Optional[str] = None, config: Config = None
+) -> RecordIterator: ...
+def unpivot_crosstabs(
+    source: Source, sheet: Optional[str] = None, config: Config = None
+) -> RecordIterator: ...
@@ -0,0 +1,267 @@
+//! 批量处理
+//!
+//! 把命令行给出的多个路径展开为工作簿列表：普通文件直接使用，目录递归查找工作簿，
+//! 含 `*`、`?`、`[...]` 且不存在的路径按通配符匹配（`**` 匹配任意层目录）。
+//! 每个工作簿单独分类，单个文件出错只记录在该文件的结果中，不影响其他文件。
+//! 多个工作簿按 `threads` 配置并行处理，结果仍按展开后的路径顺序输出。
+
+use crate::parallel::for_each_ordered;
+use crate::{classify_excel_sheets_with_config, ClassifiedSheet, ClassifierConfig};
+use globset::GlobBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+// 目录中按扩展名识别的工作簿
+const WORKBOOK_EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
+/// 单个工作簿的批量分类结果，成功时有 `sheets`，失败时有 `error`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkbookResult {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheets: Option<Vec<ClassifiedSheet>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl WorkbookResult {
+    fn failed(path: String, error: String) -> Self {
+        WorkbookResult {
+            path,
+            sheets: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// 路径是否按通配符处理：含有通配符且不是已存在的文件或目录（如 "report[2024].xlsx"）
+pub fn is_glob_pattern(input: &str) -> bool {
+    has_wildcard(input) && !Path::new(input).exists()
+}
+
+fn has_wildcard(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+/// 把文件、目录和通配符展开为工作簿路径，按输入顺序排列，同一输入内按路径排序
+/// 无法展开的部分（无法读取的目录、无效的通配符、没有匹配的通配符）以 `Err(输入, 错误信息)` 返回
+pub fn expand_workbook_paths(inputs: &[String]) -> Vec<Result<PathBuf, (String, String)>> {
+    let mut result = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        let entries: Vec<Result<PathBuf, String>> = if is_glob_pattern(input) {
+            let matches = glob_files(input);
+            if matches.is_empty() {
+                vec![Err("No files match pattern".to_string())]
+            } else {
+                matches
+            }
+        } else if path.is_dir() {
+            walk_files(path, None)
+                .filter(|entry| entry.as_ref().map_or(true, |file| is_workbook_file(file)))
+                .collect()
+        } else {
+            // 普通文件即使扩展名不是工作簿也交给分类函数，由它按内容识别或报错
+            vec![Ok(path.to_path_buf())]
+        };
+        result.extend(
+            entries
+                .into_iter()
+                .map(|entry| entry.map_err(|e| (input.clone(), e))),
+        );
+    }
+    result
+}
+
+/// 并行分类所有输入中的工作簿，结果按路径顺序依次交给 `on_result`
+/// 工作簿之间已经并行，单个工作簿内的工作表不再另开线程
+pub fn classify_workbooks_with_config<F: FnMut(WorkbookResult)>(
+    inputs: &[String],
+    config: &ClassifierConfig,
+    on_result: F,
+) {
+    let workbook_config = ClassifierConfig {
+        threads: 1,
+        ..config.clone()
+    };
+    let entries = expand_workbook_paths(inputs);
+    for_each_ordered(
+        &entries,
+        config.threads,
+        |entry| match entry {
+            Ok(path) => classify_workbook(path, &workbook_config),
+            Err((input, error)) => WorkbookResult::failed(input.clone(), error.clone()),
+        },
+        on_result,
+    );
+}
+
+/// 分类单个工作簿，错误记录在结果中
+fn classify_workbook(path: &Path, config: &ClassifierConfig) -> WorkbookResult {
+    let display = path.to_string_lossy().into_owned();
+    match classify_excel_sheets_with_config(&display, config) {
+        Ok(sheets) => WorkbookResult {
+            path: display,
+            sheets: Some(sheets),
+            error: None,
+        },
+        Err(e) => WorkbookResult::failed(display, e.to_string()),
+    }
+}
+
+/// 工作簿扩展名，跳过 Excel 打开文件时生成的 `~$` 锁文件
+fn is_workbook_file(path: &Path) -> bool {
+    let is_lock_file = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("~$"));
+    let is_workbook = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            WORKBOOK_EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        });
+    is_workbook && !is_lock_file
+}
+
+/// 递归列出目录下的文件，`max_depth` 限制向下的层数
+/// 不进入符号链接指向的目录，避免循环链接导致无限递归；无法读取的子目录作为单独的错误返回，不影响其他文件
+fn walk_files(
+    dir: &Path,
+    max_depth: Option<usize>,
+) -> impl Iterator<Item = Result<PathBuf, String>> {
+    let mut walker = WalkDir::new(dir).sort_by_file_name();
+    if let Some(depth) = max_depth {
+        walker = walker.max_depth(depth);
+    }
+    walker.into_iter().filter_map(|entry| match entry {
+        Ok(entry) if entry.file_type().is_dir() => None,
+        Ok(entry) if entry.path_is_symlink() && entry.path().is_dir() => None,
+        Ok(entry) => Some(Ok(entry.into_path())),
+        Err(e) => Some(Err(e.to_string())),
+    })
+}
+
+/// 按通配符查找文件：从不含通配符的前缀目录开始遍历，匹配相对路径
+/// 只有含 `**` 的通配符才递归到任意层，否则只遍历通配符所在的层数
+fn glob_files(pattern: &str) -> Vec<Result<PathBuf, String>> {
+    let components: Vec<&str> = Path::new(pattern)
+        .components()
+        .map(|c| match c {
+            Component::Normal(s) => s.to_str().unwrap_or(""),
+            Component::RootDir => "/",
+            Component::CurDir => ".",
+            Component::ParentDir => "..",
+            Component::Prefix(p) => p.as_os_str().to_str().unwrap_or(""),
+        })
+        .collect();
+    let literal = components.iter().take_while(|c| !has_wildcard(c)).count();
+
+    let base: PathBuf = if literal == 0 {
+        PathBuf::from(".")
+    } else {
+        components[..literal].iter().collect()
+    };
+    let pattern_parts = &components[literal..];
+    let matcher = match GlobBuilder::new(&pattern_parts.join("/"))
+        .literal_separator(true)
+        .build()
+    {
+        Ok(glob) => glob.compile_matcher(),
+        Err(e) => return vec![Err(e.to_string())],
+    };
+    let max_depth =
+        (!pattern_parts.iter().any(|part| part.contains("**"))).then_some(pattern_parts.len());
+
+    walk_files(&base, max_depth)
+        .filter_map(|entry| match entry {
+            Ok(file) => {
+                let relative = file.strip_prefix(&base).unwrap_or(&file);
+                if !matcher.is_match(relative) {
+                    None
+                } else if literal == 0 {
+                    Some(Ok(strip_cur_dir(file)))
+                } else {
+                    Some(Ok(file))
+                }
+            }
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
+
+/// 去掉以当前目录为起点时加上的 `./` 前缀
+fn strip_cur_dir(path: PathBuf) -> PathBuf {
+    path.strip_prefix(".")
+        .map(Path::to_path_buf)
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_glob_depth_and_symlink_loop() {
+        // 文件名含 `[` 的已有文件按普通路径处理；`*` 不递归，`**` 递归但不进入循环的符号链接
+        let root = std::env::temp_dir().join(format!("layout_view_{}_batch", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        for file in ["a.xlsx", "report[2024].xlsx", "sub/b.xlsx"] {
+            std::fs::write(root.join(file), b"").unwrap();
+        }
+        std::os::unix::fs::symlink(&root, root.join("sub/loop")).unwrap();
+
+        let root_str = root.to_string_lossy().into_owned();
+        let expand = |input: String| -> Vec<String> {
+            expand_workbook_paths(&[input])
+                .into_iter()
+                .map(|entry| {
+                    let path = entry.unwrap();
+                    path.strip_prefix(&root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect()
+        };
+        let bracket = format!("{}/report[2024].xlsx", root_str);
+        let shallow = expand(format!("{}/*.xlsx", root_str));
+        let deep = expand(format!("{}/**/*.xlsx", root_str));
+        let dir = expand(root_str.clone());
+        let bracket_is_glob = is_glob_pattern(&bracket);
+        let bracket_paths = expand(bracket);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(!bracket_is_glob);
+        assert_eq!(bracket_paths, ["report[2024].xlsx"]);
+        assert_eq!(shallow, ["a.xlsx", "report[2024].xlsx"]);
+        assert_eq!(deep, ["a.xlsx", "report[2024].xlsx", "sub/b.xlsx"]);
+        assert_eq!(dir, deep);
+    }
+
+    #[test]
+    fn test_expand_workbook_paths() {
+        let inputs = vec![
+            "tests".to_string(),
+            "tests/test1_*.xlsx".to_string(),
+            "tests/none_*.xlsx".to_string(),
+        ];
+        let paths = expand_workbook_paths(&inputs);
+        let ok: Vec<String> = paths
+            .iter()
+            .filter_map(|p| p.as_ref().ok())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .collect();
+
+        // 目录展开为全部 5 个工作簿，通配符匹配 2 个
+        assert_eq!(ok.len(), 7);
+        assert_eq!(ok[0], "tests/test1_data.xlsx");
+        assert_eq!(ok[5..], ["tests/test1_data.xlsx", "tests/test1_form.xlsx"]);
+        assert!(matches!(paths.last(), Some(Err((input, _))) if input == "tests/none_*.xlsx"));
+    }
+}
@@ -0,0 +1,240 @@
+//! 分类阈值配置
+//!
+//! `ClassifierConfig` 的默认值与内置分类规则一致，可以从 TOML 或 JSON 文件加载，
+//! 文件中只需写出要调整的字段，其余字段使用默认值。
+
+use crate::{DensityMode, LayoutError, NumberLocale, SamplingConfig};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+/// 工作表分类使用的阈值
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClassifierConfig {
+    pub high_density: f64,                // 密度高于该值直接判为数据表
+    pub tall_form_aspect_ratio: f64,      // 高瘦表单：宽高比下限
+    pub tall_form_max_cols: u32,          // 高瘦表单：列数上限
+    pub tall_form_min_density: f64,       // 高瘦表单：密度阈值
+    pub data_density: f64,                // 多列数据表：密度阈值
+    pub data_min_cols: u32,               // 多列数据表：列数需大于该值
+    pub row_consistency: f64,             // 多列数据表：行一致性阈值
+    pub consistent_density: f64,          // 行结构一致的数据表：密度阈值
+    pub high_row_consistency: f64,        // 行结构一致的数据表：行一致性阈值
+    pub wide_table_min_cols: u32,         // 宽表：列数需大于该值
+    pub wide_table_aspect_ratio: f64,     // 宽表：宽高比上限
+    pub wide_table_min_density: f64,      // 宽表：密度阈值
+    pub dense_mixed_density: f64,         // 类型混合的数据表：密度阈值
+    pub dense_mixed_type_mix: f64,        // 类型混合的数据表：混合度阈值
+    pub mixed_density: f64,               // 中等密度的混合数据表：密度阈值
+    pub mixed_type_mix: f64,              // 中等密度的混合数据表：混合度阈值
+    pub mixed_min_cols: u32,              // 中等密度的混合数据表：列数需大于该值
+    pub merged_form_ratio: f64,           // 合并单元格表单：合并单元格占比需大于该值
+    pub calendar_min_weekdays: u32,       // 日历：开头几行中至少出现的星期名称个数
+    pub calendar_ratio: f64,              // 日历：日期数字、星期名称占比需大于该值
+    pub notes_min_text_length: f64,       // 说明文字：文本平均字符数需大于该值
+    pub notes_text_ratio: f64,            // 说明文字：文本单元格占比需大于该值
+    pub list_min_rows: u32,               // 清单和代码对照表：最少行数
+    pub lookup_max_cols: u32,             // 代码对照表：列数上限
+    pub lookup_key_unique_ratio: f64,     // 代码对照表：首列不同取值比例下限
+    pub lookup_key_max_length: f64,       // 代码对照表：首列平均字符数上限
+    pub crosstab_period_ratio: f64,       // 交叉表：表头中期间标签的比例下限
+    pub crosstab_row_label_ratio: f64,    // 交叉表：首列文本标签的比例下限
+    pub crosstab_numeric_ratio: f64,      // 交叉表：表体数值单元格的比例下限
+    pub confidence_margin: f64,           // 相对余量达到该值时置信度为1
+    pub near_miss_margin: f64,            // 相对差距不超过该值的未命中规则列为接近命中
+    pub sampling: SamplingConfig,         // 计算指标时的采样方式
+    pub number_locale: NumberLocale,      // 数值字符串的小数点约定
+    pub density_mode: DensityMode,        // 计算密度时是否把合并单元格计为有数据
+    pub visibility: Vec<SheetVisibility>, // 参与分析的工作表可见性，默认只分析可见工作表
+    pub sheets: Vec<String>,              // 只分析这些名称的工作表，为空时分析全部工作表
+    pub threads: usize, // 并行分析的线程数，0 表示按 CPU 核心数（wasm32 上总是单线程）
+}
+
+/// 工作表可见性
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetVisibility {
+    Visible,
+    Hidden,
+    VeryHidden,
+}
+
+impl SheetVisibility {
+    /// 全部三种可见性
+    pub const ALL: [SheetVisibility; 3] = [
+        SheetVisibility::Visible,
+        SheetVisibility::Hidden,
+        SheetVisibility::VeryHidden,
+    ];
+
+    /// 按名称解析，忽略大小写、下划线和连字符（"very_hidden"、"VeryHidden"、"very-hidden" 均可）
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match normalized.as_str() {
+            "visible" => Some(SheetVisibility::Visible),
+            "hidden" => Some(SheetVisibility::Hidden),
+            "veryhidden" => Some(SheetVisibility::VeryHidden),
+            _ => None,
+        }
+    }
+}
+
+impl From<calamine::SheetVisible> for SheetVisibility {
+    fn from(visible: calamine::SheetVisible) -> Self {
+        match visible {
+            calamine::SheetVisible::Visible => SheetVisibility::Visible,
+            calamine::SheetVisible::Hidden => SheetVisibility::Hidden,
+            calamine::SheetVisible::VeryHidden => SheetVisibility::VeryHidden,
+        }
+    }
+}
+
+impl Default for ClassifierConfig {
+    fn default() -> Self {
+        ClassifierConfig {
+            high_density: 0.70,
+            tall_form_aspect_ratio: 4.0,
+            tall_form_max_cols: 4,
+            tall_form_min_density: 0.35,
+            data_density: 0.46,
+            data_min_cols: 4,
+            row_consistency: 0.50,
+            consistent_density: 0.40,
+            high_row_consistency: 0.80,
+            wide_table_min_cols: 10,
+            wide_table_aspect_ratio: 0.5,
+            wide_table_min_density: 0.35,
+            dense_mixed_density: 0.46,
+            dense_mixed_type_mix: 0.35,
+            mixed_density: 0.35,
+            mixed_type_mix: 0.35,
+            mixed_min_cols: 5,
+            merged_form_ratio: 0.25,
+            calendar_min_weekdays: 5,
+            calendar_ratio: 0.6,
+            notes_min_text_length: 30.0,
+            notes_text_ratio: 0.8,
+            list_min_rows: 3,
+            lookup_max_cols: 3,
+            lookup_key_unique_ratio: 0.95,
+            lookup_key_max_length: 12.0,
+            crosstab_period_ratio: 0.5,
+            crosstab_row_label_ratio: 0.8,
+            crosstab_numeric_ratio: 0.8,
+            confidence_margin: 0.25,
+            near_miss_margin: 0.10,
+            sampling: SamplingConfig::default(),
+            number_locale: NumberLocale::DecimalPoint,
+            density_mode: DensityMode::Cells,
+            visibility: vec![SheetVisibility::Visible],
+            sheets: Vec::new(),
+            threads: 0,
+        }
+    }
+}
+
+impl ClassifierConfig {
+    /// 是否分析指定可见性的工作表
+    pub fn includes_visibility(&self, visible: calamine::SheetVisible) -> bool {
+        self.visibility.contains(&visible.into())
+    }
+
+    /// 是否分析指定名称的工作表，未被选中的工作表不会从工作簿中读取
+    pub fn includes_sheet(&self, name: &str) -> bool {
+        self.sheets.is_empty() || self.sheets.iter().any(|sheet| sheet == name)
+    }
+
+    /// 检查无法用类型表达的取值约束：`confidence_margin` 必须为正数（它是计算置信度时的除数）
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        if self.confidence_margin.is_nan() || self.confidence_margin <= 0.0 {
+            return Err(LayoutError::Config(format!(
+                "confidence_margin must be positive, got {}",
+                self.confidence_margin
+            )));
+        }
+        Ok(())
+    }
+
+    /// 从 JSON 字符串加载配置
+    pub fn from_json_str(json: &str) -> Result<Self, LayoutError> {
+        let config: Self =
+            serde_json::from_str(json).map_err(|e| LayoutError::Config(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从 TOML 字符串加载配置
+    pub fn from_toml_str(toml: &str) -> Result<Self, LayoutError> {
+        let config: Self = toml::from_str(toml).map_err(|e| LayoutError::Config(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从文件加载配置，`.toml` 按 TOML 解析，`.json` 按 JSON 解析，
+    /// 其他扩展名先尝试 JSON 再尝试 TOML
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, LayoutError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("toml") => Self::from_toml_str(&content),
+            Some("json") => Self::from_json_str(&content),
+            _ => Self::from_json_str(&content).or_else(|_| Self::from_toml_str(&content)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_config() {
+        let config =
+            ClassifierConfig::from_toml_str("high_density = 0.8\nmixed_min_cols = 7\n").unwrap();
+        assert_eq!(config.high_density, 0.8);
+        assert_eq!(config.mixed_min_cols, 7);
+        assert_eq!(config.data_density, 0.46);
+
+        let config =
+            ClassifierConfig::from_toml_str("[sampling]\nstrategy = \"head_tail\"\n").unwrap();
+        assert_eq!(config.sampling.strategy, crate::SamplingStrategy::HeadTail);
+        assert_eq!(config.sampling.sample_rows, 100);
+
+        let config =
+            ClassifierConfig::from_toml_str("visibility = [\"hidden\", \"very_hidden\"]\n")
+                .unwrap();
+        assert!(!config.includes_visibility(calamine::SheetVisible::Visible));
+        assert!(config.includes_visibility(calamine::SheetVisible::VeryHidden));
+        assert_eq!(
+            SheetVisibility::from_name("VeryHidden"),
+            Some(SheetVisibility::VeryHidden)
+        );
+
+        // 每条规则的阈值相互独立，调整一条规则不影响其他规则
+        let config = ClassifierConfig::from_json_str(r#"{"wide_table_min_density": 0.3}"#).unwrap();
+        assert_eq!(config.wide_table_min_density, 0.3);
+        assert_eq!(config.tall_form_min_density, 0.35);
+        assert_eq!(config.mixed_density, 0.35);
+        assert_eq!(config.high_density, 0.70);
+
+        assert!(ClassifierConfig::from_json_str(r#"{"high_density": "high"}"#).is_err());
+        // 拼写错误的字段应报错，而不是被静默忽略
+        assert!(ClassifierConfig::from_json_str(r#"{"hgih_density": 0.8}"#).is_err());
+        // confidence_margin 是计算置信度时的除数，必须为正数
+        let error = ClassifierConfig::from_json_str(r#"{"confidence_margin": 0}"#).unwrap_err();
+        assert!(matches!(error, LayoutError::Config(_)));
+        assert!(ClassifierConfig::from_toml_str("confidence_margin = -0.1\n").is_err());
+        assert!(ClassifierConfig::from_toml_str("confidence_margin = nan\n").is_err());
+    }
+}
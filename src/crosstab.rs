@@ -0,0 +1,423 @@
+//! 交叉表识别与逆透视
+//!
+//! 交叉表（透视报表）左侧若干列是行标签，上方若干行是列标签（期间等），其余是数值表体。
+//! 识别出这三部分后，可以把表体逐个单元格展开为长格式记录：
+//! `(行标签…, 列标签…, value)`。
+//! 多层列标签与数据表的多层表头共用同一套处理（横向合并、沿用左侧的分组标题和层级结构）。
+
+use crate::datatype::{cell_category, CellCategory};
+use crate::extract::cell_to_json;
+use crate::header::{build_tree, dedup_names, level_labels, HeaderNode};
+use crate::numeric::NumberLocale;
+use crate::shape::{is_period_label, is_total_label, period_kind};
+use crate::{format_cell, is_empty_cell, MergedRegion, Record};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// 列标签行、行标签列最多的数量
+const MAX_HEADER_ROWS: u32 = 5;
+const MAX_ROW_HEADER_COLS: u32 = 5;
+// 表体至少的列数
+const MIN_VALUE_COLUMNS: u32 = 2;
+// 表体中数值单元格的最低占比
+const MIN_BODY_NUMERIC_RATIO: f64 = 0.8;
+
+/// 识别出的交叉表结构，行列号从 0 开始
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CrosstabLayout {
+    pub row_header_cols: Vec<u32>,    // 行标签所在的列
+    pub column_header_rows: Vec<u32>, // 列标签所在的行
+    pub body_first_row: u32,          // 数值表体的范围
+    pub body_first_col: u32,
+    pub body_end_row: u32,
+    pub body_end_col: u32,
+    pub row_key_names: Vec<String>, // 长格式中行标签的字段名，与 row_header_cols 对应
+    pub column_key_names: Vec<String>, // 长格式中列标签的字段名，与 column_header_rows 对应
+    pub value_name: String,         // 长格式中数值的字段名
+    #[serde(default)]
+    pub column_header_tree: Vec<HeaderNode>, // 列标签的层级结构，决定每个表体列的列标签
+}
+
+/// 在给定范围内识别交叉表结构，不符合交叉表形态时返回 None
+/// `bounds` 为 (起始行, 起始列, 结束行, 结束列)
+pub(crate) fn detect_crosstab(
+    range: &calamine::Range<calamine::Data>,
+    merged_regions: &[MergedRegion],
+    bounds: (u32, u32, u32, u32),
+    locale: NumberLocale,
+) -> Option<CrosstabLayout> {
+    let (start_row, start_col, end_row, end_col) = bounds;
+    if end_row <= start_row || end_col < start_col + MIN_VALUE_COLUMNS {
+        return None;
+    }
+    let cell = |row: u32, col: u32| range.get_value((row, col)).filter(|c| !is_empty_cell(c));
+    let is_numeric = |c: &calamine::Data| cell_category(c, locale) == Some(CellCategory::Numeric);
+
+    // 列标签行：从上往下，首列右侧以标签为主的行；右侧全空的行（标题等）跳过
+    let mut column_header_rows = Vec::new();
+    let mut body_first_row = start_row;
+    while body_first_row <= end_row && body_first_row < start_row + MAX_HEADER_ROWS {
+        let cells: Vec<&calamine::Data> = (start_col + 1..=end_col)
+            .filter_map(|col| cell(body_first_row, col))
+            .collect();
+        let values = cells
+            .iter()
+            .filter(|c| is_numeric(c) && !is_period_label(&format_cell(c)))
+            .count();
+        if !cells.is_empty() && values * 2 >= cells.len() {
+            break;
+        }
+        if !cells.is_empty() {
+            column_header_rows.push(body_first_row);
+        }
+        body_first_row += 1;
+    }
+    if column_header_rows.is_empty() || body_first_row > end_row {
+        return None;
+    }
+
+    // 行标签列：从左往右，表体行中以文本为主的列
+    let mut row_header_cols = Vec::new();
+    let mut body_first_col = start_col;
+    while body_first_col < end_col && body_first_col < start_col + MAX_ROW_HEADER_COLS {
+        let cells: Vec<&calamine::Data> = (body_first_row..=end_row)
+            .filter_map(|row| cell(row, body_first_col))
+            .collect();
+        let numeric = cells.iter().filter(|c| is_numeric(c)).count();
+        if cells.is_empty() || numeric * 2 >= cells.len() {
+            break;
+        }
+        row_header_cols.push(body_first_col);
+        body_first_col += 1;
+    }
+    if row_header_cols.is_empty() || end_col + 1 < body_first_col + MIN_VALUE_COLUMNS {
+        return None;
+    }
+
+    // 表体应以数值为主
+    let mut body_cells = 0;
+    let mut body_numeric = 0;
+    for row in body_first_row..=end_row {
+        for col in body_first_col..=end_col {
+            if let Some(c) = cell(row, col) {
+                body_cells += 1;
+                body_numeric += is_numeric(c) as u32;
+            }
+        }
+    }
+    if body_cells == 0 || (body_numeric as f64 / body_cells as f64) < MIN_BODY_NUMERIC_RATIO {
+        return None;
+    }
+
+    // 行标签字段名取最后一个列标签行中对应的单元格（左上角），空白时按序号命名
+    let last_header_row = *column_header_rows.last()?;
+    let key_name = |prefix: &str, index: usize, count: usize| {
+        if count == 1 {
+            prefix.to_string()
+        } else {
+            format!("{}_{}", prefix, index + 1)
+        }
+    };
+    let row_key_names = row_header_cols.iter().enumerate().map(|(index, &col)| {
+        cell(last_header_row, col)
+            .map(format_cell)
+            .unwrap_or_else(|| key_name("row_key", index, row_header_cols.len()))
+    });
+    // 列标签字段名取该层期间的粒度（year、quarter、month 等），粒度不一致时按序号命名
+    let labels = level_labels(
+        range,
+        merged_regions,
+        &column_header_rows,
+        body_first_col,
+        end_col,
+    );
+    let column_key_names = labels.iter().enumerate().map(|(index, level)| {
+        let mut kinds = level
+            .iter()
+            .flatten()
+            .filter(|label| !is_total_label(label))
+            .map(|label| period_kind(label));
+        match kinds.next() {
+            Some(Some(kind)) if kinds.all(|k| k == Some(kind)) => kind.to_string(),
+            _ => key_name("column_key", index, column_header_rows.len()),
+        }
+    });
+    let mut names = dedup_names(
+        row_key_names
+            .chain(column_key_names)
+            .chain(std::iter::once("value".to_string())),
+    );
+    let value_name = names.pop()?;
+    let column_key_names = names.split_off(row_header_cols.len());
+    let column_header_tree = build_tree(
+        &labels,
+        &column_header_rows,
+        0,
+        body_first_col,
+        body_first_col,
+        end_col,
+    );
+
+    Some(CrosstabLayout {
+        row_header_cols,
+        column_header_rows,
+        body_first_row,
+        body_first_col,
+        body_end_row: end_row,
+        body_end_col: end_col,
+        row_key_names: names,
+        column_key_names,
+        value_name,
+        column_header_tree,
+    })
+}
+
+/// 把交叉表表体逐个单元格展开为长格式记录的迭代器
+/// 空白单元格和汇总行列（合计、Total 等）不展开，避免重复计数
+pub struct UnpivotIter<'a> {
+    range: &'a calamine::Range<calamine::Data>,
+    layout: CrosstabLayout,
+    column_keys: Vec<Vec<Value>>, // 每个表体列的列标签
+    row_keys: Vec<Value>,         // 当前行的行标签（空白时沿用上一行）
+    row: u32,
+    col: u32,
+}
+
+/// 按交叉表结构逐个单元格生成长格式记录
+/// `layout` 可能来自反序列化：表体超出工作表的部分截去，表体范围颠倒或在工作表之外时不生成记录
+pub fn unpivot_range<'a>(
+    range: &'a calamine::Range<calamine::Data>,
+    layout: &CrosstabLayout,
+) -> UnpivotIter<'a> {
+    let mut layout = layout.clone();
+    let width = range.end().map_or(0, |(end_row, end_col)| {
+        layout.body_end_row = layout.body_end_row.min(end_row);
+        layout.body_end_col = layout.body_end_col.min(end_col);
+        layout
+            .body_end_col
+            .checked_sub(layout.body_first_col)
+            .map_or(0, |width| width as usize + 1)
+    });
+    let mut column_keys: Vec<Vec<Value>> =
+        vec![vec![Value::Null; layout.column_header_rows.len()]; width];
+    fill_column_keys(&layout.column_header_tree, &layout, &mut column_keys);
+
+    UnpivotIter {
+        range,
+        column_keys,
+        row_keys: vec![Value::Null; layout.row_header_cols.len()],
+        row: layout.body_first_row,
+        col: layout.body_first_col,
+        layout,
+    }
+}
+
+/// 按列标签的层级结构填写每个表体列各层的列标签，节点覆盖的列都取该节点的标题
+fn fill_column_keys(nodes: &[HeaderNode], layout: &CrosstabLayout, keys: &mut [Vec<Value>]) {
+    for node in nodes {
+        let level = layout
+            .column_header_rows
+            .iter()
+            .position(|&row| row == node.row);
+        for col in node.first_col..=node.end_col {
+            let offset = col.wrapping_sub(layout.body_first_col) as usize;
+            if let Some(key) = level.and_then(|level| keys.get_mut(offset)?.get_mut(level)) {
+                *key = Value::String(node.label.clone());
+            }
+        }
+        fill_column_keys(&node.children, layout, keys);
+    }
+}
+
+impl UnpivotIter<'_> {
+    /// 进入新的一行时更新行标签，纵向合并或留空的标签沿用上一行
+    fn load_row_keys(&mut self) {
+        for (index, &col) in self.layout.row_header_cols.iter().enumerate() {
+            let value = self
+                .range
+                .get_value((self.row, col))
+                .map(cell_to_json)
+                .unwrap_or(Value::Null);
+            if !value.is_null() {
+                self.row_keys[index] = value;
+            }
+        }
+    }
+}
+
+impl Iterator for UnpivotIter<'_> {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Record> {
+        // 没有表体列（结构无效）时不生成记录
+        while !self.column_keys.is_empty() && self.row <= self.layout.body_end_row {
+            if self.col == self.layout.body_first_col {
+                self.load_row_keys();
+            }
+            let (row, col) = (self.row, self.col);
+            if self.col < self.layout.body_end_col {
+                self.col += 1;
+            } else {
+                self.row += 1;
+                self.col = self.layout.body_first_col;
+            }
+
+            let Some(cell) = self
+                .range
+                .get_value((row, col))
+                .filter(|c| !is_empty_cell(c))
+            else {
+                continue;
+            };
+            let column_keys = &self.column_keys[(col - self.layout.body_first_col) as usize];
+            if self.row_keys.iter().chain(column_keys).any(is_total_key) {
+                continue;
+            }
+
+            let mut values = Map::new();
+            let names = self
+                .layout
+                .row_key_names
+                .iter()
+                .chain(&self.layout.column_key_names);
+            for (name, key) in names.zip(self.row_keys.iter().chain(column_keys)) {
+                values.insert(name.clone(), key.clone());
+            }
+            values.insert(self.layout.value_name.clone(), cell_to_json(cell));
+            return Some(Record { row, values });
+        }
+        None
+    }
+}
+
+fn is_total_key(key: &Value) -> bool {
+    matches!(key, Value::String(s) if is_total_label(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Data, Range};
+
+    fn text(s: &str) -> Data {
+        Data::String(s.to_string())
+    }
+
+    #[test]
+    fn test_detect_and_unpivot_crosstab() {
+        // 第 0 行标题，第 1、2 行两层列标签（2024 横向合并覆盖 Q1、Q2），
+        // 左侧两列行标签（大区纵向合并），最后一列是合计
+        let mut range = Range::new((0, 0), (5, 4));
+        range.set_value((0, 0), text("销售报表"));
+        range.set_value((1, 2), Data::Int(2024));
+        range.set_value((1, 4), text("合计"));
+        range.set_value((2, 0), text("大区"));
+        range.set_value((2, 1), text("城市"));
+        range.set_value((2, 2), text("Q1"));
+        range.set_value((2, 3), text("Q2"));
+        let rows = [("华东", "上海"), ("", "杭州"), ("华南", "广州")];
+        for (offset, (area, city)) in rows.iter().enumerate() {
+            let row = offset as u32 + 3;
+            if !area.is_empty() {
+                range.set_value((row, 0), text(area));
+            }
+            range.set_value((row, 1), text(city));
+            range.set_value((row, 2), Data::Float(10.0 * row as f64));
+            range.set_value((row, 3), Data::Float(20.0 * row as f64));
+            range.set_value((row, 4), Data::Float(30.0 * row as f64));
+        }
+
+        let layout = detect_crosstab(&range, &[], (0, 0, 5, 4), NumberLocale::Auto).unwrap();
+        assert_eq!(layout.row_header_cols, vec![0, 1]);
+        assert_eq!(layout.column_header_rows, vec![1, 2]);
+        assert_eq!((layout.body_first_row, layout.body_first_col), (3, 2));
+        assert_eq!(layout.row_key_names, vec!["大区", "城市"]);
+        assert_eq!(layout.column_key_names, vec!["year", "quarter"]);
+        assert_eq!(layout.column_header_tree.len(), 2);
+        assert_eq!(layout.column_header_tree[0].children.len(), 2);
+
+        let records: Vec<Record> = unpivot_range(&range, &layout).collect();
+        // 3 行 × 2 个季度，合计列不展开
+        assert_eq!(records.len(), 6);
+        assert_eq!(
+            Value::Object(records[2].values.clone()),
+            serde_json::json!({
+                "大区": "华东", "城市": "杭州", "year": "2024", "quarter": "Q1", "value": 40
+            })
+        );
+    }
+
+    #[test]
+    fn test_unpivot_merged_column_header() {
+        // "2024" 横向合并覆盖 Q1、Q2，右侧 "累计" 上方空白且不在合并区域内，不沿用 "2024"
+        let mut range = Range::new((0, 0), (2, 3));
+        range.set_value((0, 1), Data::Int(2024));
+        range.set_value((1, 0), text("地区"));
+        range.set_value((1, 1), text("Q1"));
+        range.set_value((1, 2), text("Q2"));
+        range.set_value((1, 3), text("累计"));
+        range.set_value((2, 0), text("华东"));
+        for col in 1..4 {
+            range.set_value((2, col), Data::Float(col as f64));
+        }
+        let merged = [MergedRegion {
+            first_row: 0,
+            first_col: 1,
+            end_row: 0,
+            end_col: 2,
+        }];
+
+        let layout = detect_crosstab(&range, &merged, (0, 0, 2, 3), NumberLocale::Auto).unwrap();
+        assert_eq!(layout.column_key_names, vec!["year", "column_key_2"]);
+        let records: Vec<Value> = unpivot_range(&range, &layout)
+            .map(|r| Value::Object(r.values))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                serde_json::json!({"地区": "华东", "year": "2024", "column_key_2": "Q1", "value": 1}),
+                serde_json::json!({"地区": "华东", "year": "2024", "column_key_2": "Q2", "value": 2}),
+                serde_json::json!({"地区": "华东", "year": null, "column_key_2": "累计", "value": 3}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unpivot_invalid_layout() {
+        // 反序列化得到的结构：表体范围颠倒时不生成记录，超出工作表的部分截去
+        let mut range = Range::new((0, 0), (1, 2));
+        range.set_value((0, 1), text("Q1"));
+        range.set_value((0, 2), text("Q2"));
+        range.set_value((1, 0), text("华东"));
+        range.set_value((1, 1), Data::Float(1.0));
+        range.set_value((1, 2), Data::Float(2.0));
+        let layout = detect_crosstab(&range, &[], (0, 0, 1, 2), NumberLocale::Auto).unwrap();
+
+        let reversed = CrosstabLayout {
+            body_first_col: 2,
+            body_end_col: 1,
+            ..layout.clone()
+        };
+        assert_eq!(unpivot_range(&range, &reversed).count(), 0);
+        let oversized = CrosstabLayout {
+            body_end_row: u32::MAX,
+            body_end_col: u32::MAX,
+            ..layout
+        };
+        assert_eq!(unpivot_range(&range, &oversized).count(), 2);
+    }
+
+    #[test]
+    fn test_detect_crosstab_rejects_text_body() {
+        let mut range = Range::new((0, 0), (2, 2));
+        for col in 0..3 {
+            range.set_value((0, col), text("列"));
+            range.set_value((1, col), text("文本"));
+            range.set_value((2, col), text("文本"));
+        }
+        assert_eq!(
+            detect_crosstab(&range, &[], (0, 0, 2, 2), NumberLocale::Auto),
+            None
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! 单元格数据类型识别
+//!
+//! 在数值/文本之外识别布尔值、错误值、日期、日期时间、时间和时长。Excel 原生的日期时间单元格
+//! （`DateTime`、`DateTimeIso`、`DurationIso`）和常见写法的日期字符串
+//! （"2024-01-05"、"2024/1/5"、"2024年1月5日"、"2024-01-05 08:30"、"PT1H30M" 等）都归入对应类别。
+//! 错误值包括 Excel 错误单元格，以及导出时被写成文本的错误字面量（"#N/A"、"#REF!" 等）。
+
+use crate::numeric::{is_numeric_cell, NumberLocale};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// 单元格数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CellCategory {
+    Numeric,
+    Text,
+    Date,
+    DateTime,
+    Time,
+    Duration,
+    Bool,
+    Error,
+}
+
+// 以文本形式出现的 Excel 错误值
+const ERROR_LITERALS: [&str; 9] = [
+    "#DIV/0!", "#N/A", "#NAME?", "#NULL!", "#NUM!", "#REF!", "#VALUE!", "#DATA!", "#SPILL!",
+];
+
+lazy_static! {
+    // 年-月-日，分隔符为 - / . 或 年月日
+    static ref DATE_RE: Regex = Regex::new(
+        r"^(\d{4})(?:[-/.](\d{1,2})[-/.](\d{1,2})|年(\d{1,2})月(\d{1,2})日)$"
+    )
+    .unwrap();
+    // 时:分[:秒[.毫秒]]，可带 AM/PM
+    static ref TIME_RE: Regex =
+        Regex::new(r"^(\d{1,2}):([0-5]\d)(?::([0-5]\d)(?:\.\d+)?)?(?:\s*([AaPp][Mm]))?$").unwrap();
+    // 时长：小时数不少于 24 的 时:分:秒，或 ISO 8601 时长（P1DT2H、PT30M）
+    static ref CLOCK_DURATION_RE: Regex = Regex::new(r"^(\d+):[0-5]\d:[0-5]\d$").unwrap();
+    static ref ISO_DURATION_RE: Regex = Regex::new(
+        r"^-?P(?:\d+Y)?(?:\d+M)?(?:\d+W)?(?:\d+D)?(?:T(?:\d+H)?(?:\d+M)?(?:\d+(?:\.\d+)?S)?)?$"
+    )
+    .unwrap();
+    // 日期与时间之间的分隔：T 或空白
+    static ref DATETIME_SPLIT_RE: Regex = Regex::new(r"^(\S+?)(?:T|\s+)(.+)$").unwrap();
+    // 时间部分末尾的时区（Z、+08:00、+0800）
+    static ref TIMEZONE_RE: Regex = Regex::new(r"(?:Z|[+-]\d{2}:?\d{2})$").unwrap();
+}
+
+/// 判断非空单元格的数据类别，空白单元格返回 None
+pub(crate) fn cell_category(cell: &calamine::Data, locale: NumberLocale) -> Option<CellCategory> {
+    match cell {
+        calamine::Data::Empty => None,
+        calamine::Data::String(s) if s.trim().is_empty() => None,
+        calamine::Data::Error(_) => Some(CellCategory::Error),
+        calamine::Data::Bool(_) => Some(CellCategory::Bool),
+        calamine::Data::DateTime(dt) if dt.is_duration() => Some(CellCategory::Duration),
+        calamine::Data::DateTime(dt) => {
+            let value = dt.as_f64();
+            Some(if value < 1.0 {
+                CellCategory::Time
+            } else if value.fract() == 0.0 {
+                CellCategory::Date
+            } else {
+                CellCategory::DateTime
+            })
+        }
+        calamine::Data::DateTimeIso(s) => {
+            Some(temporal_string_category(s).unwrap_or(CellCategory::DateTime))
+        }
+        calamine::Data::DurationIso(_) => Some(CellCategory::Duration),
+        _ if is_numeric_cell(cell, locale) => Some(CellCategory::Numeric),
+        calamine::Data::String(_) if error_kind(cell).is_some() => Some(CellCategory::Error),
+        calamine::Data::String(s) => {
+            Some(temporal_string_category(s).unwrap_or(CellCategory::Text))
+        }
+        _ => Some(CellCategory::Text),
+    }
+}
+
+/// 错误单元格的错误类型（"#DIV/0!"、"#N/A" 等），非错误单元格返回 None
+pub(crate) fn error_kind(cell: &calamine::Data) -> Option<String> {
+    match cell {
+        calamine::Data::Error(e) => Some(e.to_string()),
+        calamine::Data::String(s) => {
+            let s = s.trim();
+            ERROR_LITERALS
+                .iter()
+                .find(|literal| s.eq_ignore_ascii_case(literal))
+                .map(|literal| literal.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// 识别日期、日期时间、时间和时长字符串
+fn temporal_string_category(s: &str) -> Option<CellCategory> {
+    let s = s.trim();
+    if is_date(s) {
+        return Some(CellCategory::Date);
+    }
+    if is_time(s) {
+        return Some(CellCategory::Time);
+    }
+    let iso_duration = ISO_DURATION_RE.is_match(s) && s.chars().any(|c| c.is_ascii_digit());
+    if CLOCK_DURATION_RE.is_match(s) || iso_duration {
+        return Some(CellCategory::Duration);
+    }
+    let caps = DATETIME_SPLIT_RE.captures(s)?;
+    let time = TIMEZONE_RE.replace(caps[2].trim(), "");
+    (is_date(&caps[1]) && is_time(time.trim())).then_some(CellCategory::DateTime)
+}
+
+fn is_date(s: &str) -> bool {
+    let Some(caps) = DATE_RE.captures(s) else {
+        return false;
+    };
+    let month = caps.get(2).or(caps.get(4)).map(|m| m.as_str());
+    let day = caps.get(3).or(caps.get(5)).map(|m| m.as_str());
+    let month: u32 = month.and_then(|m| m.parse().ok()).unwrap_or(0);
+    let day: u32 = day.and_then(|d| d.parse().ok()).unwrap_or(0);
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+fn is_time(s: &str) -> bool {
+    let Some(caps) = TIME_RE.captures(s) else {
+        return false;
+    };
+    let hour: u32 = caps[1].parse().unwrap_or(u32::MAX);
+    if caps.get(4).is_some() {
+        (1..=12).contains(&hour)
+    } else {
+        hour < 24
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::Data;
+
+    fn category(s: &str) -> Option<CellCategory> {
+        cell_category(&Data::String(s.to_string()), NumberLocale::Auto)
+    }
+
+    #[test]
+    fn test_cell_category() {
+        assert_eq!(category("2024-01-05"), Some(CellCategory::Date));
+        assert_eq!(category("2024/1/5"), Some(CellCategory::Date));
+        assert_eq!(category("2024年1月5日"), Some(CellCategory::Date));
+        assert_eq!(category("2024-13-05"), Some(CellCategory::Text));
+        assert_eq!(
+            category("2024-01-05T08:30:00Z"),
+            Some(CellCategory::DateTime)
+        );
+        assert_eq!(category("2024年1月5日 8:30"), Some(CellCategory::DateTime));
+        assert_eq!(category("08:30"), Some(CellCategory::Time));
+        assert_eq!(category("8:30 PM"), Some(CellCategory::Time));
+        assert_eq!(category("36:15:00"), Some(CellCategory::Duration));
+        assert_eq!(category("PT1H30M"), Some(CellCategory::Duration));
+        assert_eq!(category("PT"), Some(CellCategory::Text));
+        assert_eq!(category("1,234"), Some(CellCategory::Numeric));
+        assert_eq!(category("  "), None);
+        assert_eq!(category("#N/A"), Some(CellCategory::Error));
+        assert_eq!(
+            cell_category(&Data::Bool(true), NumberLocale::Auto),
+            Some(CellCategory::Bool)
+        );
+
+        let div0 = Data::Error(calamine::CellErrorType::Div0);
+        assert_eq!(
+            cell_category(&div0, NumberLocale::Auto),
+            Some(CellCategory::Error)
+        );
+        assert_eq!(error_kind(&div0), Some("#DIV/0!".to_string()));
+        assert_eq!(
+            error_kind(&Data::String("#ref!".to_string())),
+            Some("#REF!".to_string())
+        );
+
+        assert_eq!(
+            cell_category(
+                &Data::DateTimeIso("2024-01-05".to_string()),
+                NumberLocale::Auto
+            ),
+            Some(CellCategory::Date)
+        );
+        assert_eq!(
+            cell_category(&Data::DurationIso("PT2H".to_string()), NumberLocale::Auto),
+            Some(CellCategory::Duration)
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! 错误类型
+//!
+//! 库中所有可能失败的操作都返回 `LayoutError`，调用方可以按类型区分失败原因；
+//! C FFI 通过 `code()` 取得稳定的数字错误码。
+
+use std::fmt;
+
+/// 打开、分析工作簿或加载配置时的错误
+#[derive(Debug)]
+pub enum LayoutError {
+    /// 参数无效，例如 FFI 传入空指针或非 UTF-8 字符串
+    InvalidArgument(String),
+    /// 读取文件失败，例如文件不存在或没有权限
+    Io(std::io::Error),
+    /// 无法识别的工作簿格式
+    UnsupportedFormat,
+    /// 工作簿已损坏或无法解析，例如 ZIP 结构损坏
+    Workbook(calamine::Error),
+    /// 分类配置无法解析或包含未知字段
+    Config(String),
+    /// 结果无法序列化为 JSON
+    Serialization(serde_json::Error),
+    /// 内部错误：分析过程中发生 panic（仅由 C FFI 捕获后返回）
+    Panic(String),
+}
+
+impl LayoutError {
+    /// 稳定的数字错误码，0 保留给“没有错误”
+    pub fn code(&self) -> i32 {
+        match self {
+            LayoutError::InvalidArgument(_) => 1,
+            LayoutError::Io(_) => 2,
+            LayoutError::UnsupportedFormat => 3,
+            LayoutError::Workbook(_) => 4,
+            LayoutError::Config(_) => 5,
+            LayoutError::Serialization(_) => 6,
+            LayoutError::Panic(_) => 7,
+        }
+    }
+
+    /// 错误类型名称，与 `code()` 一一对应
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LayoutError::InvalidArgument(_) => "invalid_argument",
+            LayoutError::Io(_) => "io",
+            LayoutError::UnsupportedFormat => "unsupported_format",
+            LayoutError::Workbook(_) => "workbook",
+            LayoutError::Config(_) => "config",
+            LayoutError::Serialization(_) => "serialization",
+            LayoutError::Panic(_) => "panic",
+        }
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::InvalidArgument(message) => write!(f, "Invalid argument: {}", message),
+            LayoutError::Io(e) => write!(f, "I/O error: {}", e),
+            LayoutError::UnsupportedFormat => write!(f, "Cannot detect file format"),
+            LayoutError::Workbook(e) => write!(f, "Invalid workbook: {}", e),
+            LayoutError::Config(message) => write!(f, "Invalid config: {}", message),
+            LayoutError::Serialization(e) => write!(f, "Serialization error: {}", e),
+            LayoutError::Panic(message) => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LayoutError::Io(e) => Some(e),
+            LayoutError::Workbook(e) => Some(e),
+            LayoutError::Serialization(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LayoutError {
+    fn from(e: std::io::Error) -> Self {
+        LayoutError::Io(e)
+    }
+}
+
+impl From<calamine::Error> for LayoutError {
+    fn from(e: calamine::Error) -> Self {
+        match e {
+            calamine::Error::Io(e) => LayoutError::Io(e),
+            e => LayoutError::Workbook(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for LayoutError {
+    fn from(e: serde_json::Error) -> Self {
+        LayoutError::Serialization(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let missing = crate::classify_excel_sheets("tests/does_not_exist.xlsx").unwrap_err();
+        assert!(matches!(missing, LayoutError::Io(_)));
+        assert_eq!((missing.code(), missing.kind()), (2, "io"));
+
+        let config = crate::ClassifierConfig::default();
+        let unknown = crate::classify_excel_sheets_from_bytes(b"not a workbook", &config);
+        let unknown = unknown.unwrap_err();
+        assert!(matches!(unknown, LayoutError::UnsupportedFormat));
+
+        // 内容无法识别时按扩展名区分：工作簿扩展名给出解析错误，其他扩展名是不支持的格式
+        for (ext, code) in [("xlsx", 4), ("txt", 3)] {
+            let path = std::env::temp_dir().join(format!(
+                "layout_view_{}_garbage.{}",
+                std::process::id(),
+                ext
+            ));
+            std::fs::write(&path, b"not a workbook").unwrap();
+            let result = crate::classify_excel_sheets(path.to_str().unwrap());
+            let _ = std::fs::remove_file(&path);
+            assert_eq!(result.unwrap_err().code(), code, "{}", ext);
+        }
+
+        // ZIP 目录完好但压缩数据损坏
+        let mut corrupt = std::fs::read("tests/test1_data.xlsx").unwrap();
+        let len = corrupt.len();
+        corrupt[64..len / 2].fill(0);
+        let error = crate::classify_excel_sheets_from_bytes(&corrupt, &config).unwrap_err();
+        assert!(matches!(error, LayoutError::Workbook(_)), "{:?}", error);
+
+        let config = crate::ClassifierConfig::from_json_str(r#"{"hgih_density": 0.8}"#);
+        assert_eq!(config.unwrap_err().code(), 5);
+    }
+}
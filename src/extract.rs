@@ -0,0 +1,135 @@
+//! Data 工作表记录提取
+//!
+//! 以表头行之下的每一行作为一条记录，按列名输出带类型的 JSON 对象。
+//! 记录通过迭代器逐行生成，不会在内存中再保存一份整表数据。
+
+use crate::{format_cell, is_empty_cell, ClassifiedSheet};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// 一条数据记录
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    pub row: u32,                   // 记录所在行（0 起始）
+    pub values: Map<String, Value>, // 列名 -> 单元格值
+}
+
+/// 逐行生成记录的迭代器，跳过整行为空的行
+pub struct RecordIter<'a> {
+    range: &'a calamine::Range<calamine::Data>,
+    column_names: Vec<String>,
+    first_col: u32,
+    next_row: u32,
+    end_row: u32,
+}
+
+impl Iterator for RecordIter<'_> {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Record> {
+        while self.next_row <= self.end_row {
+            let row = self.next_row;
+            self.next_row += 1;
+
+            let mut values = Map::with_capacity(self.column_names.len());
+            let mut has_data = false;
+            for (offset, name) in self.column_names.iter().enumerate() {
+                let cell = self.range.get_value((row, self.first_col + offset as u32));
+                let value = cell.map(cell_to_json).unwrap_or(Value::Null);
+                has_data |= cell.is_some_and(|cell| !is_empty_cell(cell));
+                values.insert(name.clone(), value);
+            }
+
+            if has_data {
+                return Some(Record { row, values });
+            }
+        }
+        None
+    }
+}
+
+/// 根据分类结果中的范围和表头，从工作表数据中逐行提取记录
+/// 没有识别到表头时从第一行开始提取，列名为 `column_{列号}`
+pub fn records_from_range<'a>(
+    range: &'a calamine::Range<calamine::Data>,
+    sheet: &ClassifiedSheet,
+) -> RecordIter<'a> {
+    let column_names = if sheet.column_names.is_empty() {
+        (sheet.first_col..=sheet.end_col)
+            .map(|col| format!("column_{}", col))
+            .collect()
+    } else {
+        sheet.column_names.clone()
+    };
+    let next_row = match sheet.header_row {
+        Some(header_row) => header_row + 1,
+        None => sheet.first_row,
+    };
+
+    RecordIter {
+        range,
+        column_names,
+        first_col: sheet.first_col,
+        next_row,
+        end_row: sheet.end_row,
+    }
+}
+
+/// 把单元格转换为 JSON 值：数值、布尔保持原类型，日期时间为 ISO 8601 字符串，空白和错误为 null
+/// Excel 中的整数以浮点数存储，没有小数部分的浮点数输出为整数
+pub(crate) fn cell_to_json(cell: &calamine::Data) -> Value {
+    match cell {
+        calamine::Data::Int(i) => Value::from(*i),
+        calamine::Data::Float(f) if f.fract() == 0.0 && f.abs() < 9.0e15 => Value::from(*f as i64),
+        calamine::Data::Float(f) => serde_json::Number::from_f64(*f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        calamine::Data::Bool(b) => Value::Bool(*b),
+        calamine::Data::Empty | calamine::Data::Error(_) => Value::Null,
+        calamine::Data::String(s) if s.trim().is_empty() => Value::Null,
+        calamine::Data::String(s) => Value::String(s.clone()),
+        _ => Value::String(format_cell(cell)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{classify_sheet, SheetDataDensity};
+    use calamine::{Data, Range};
+
+    #[test]
+    fn test_records_from_range() {
+        let mut range = Range::new((0, 0), (4, 2));
+        range.set_value((0, 0), Data::String("Name".to_string()));
+        range.set_value((0, 1), Data::String("Qty".to_string()));
+        range.set_value((0, 2), Data::String("Paid".to_string()));
+        range.set_value((1, 0), Data::String("apple".to_string()));
+        range.set_value((1, 1), Data::Int(3));
+        range.set_value((1, 2), Data::Bool(true));
+        // 第 2 行整行为空，应被跳过
+        range.set_value((3, 0), Data::String("pear".to_string()));
+        range.set_value((3, 1), Data::Float(1.5));
+        range.set_value((4, 0), Data::String("fig".to_string()));
+        range.set_value((4, 1), Data::Int(7));
+        range.set_value((4, 2), Data::Bool(false));
+
+        let density: SheetDataDensity = crate::analyze_sheet(
+            "Sheet1",
+            calamine::SheetVisible::Visible,
+            &range,
+            &[],
+            &crate::ClassifierConfig::default(),
+        );
+        let sheet = classify_sheet(&density);
+        assert_eq!(sheet.header_row, Some(0));
+
+        let records: Vec<Record> = records_from_range(&range, &sheet).collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].row, 1);
+        assert_eq!(
+            Value::Object(records[1].values.clone()),
+            serde_json::json!({"Name": "pear", "Qty": 1.5, "Paid": null})
+        );
+    }
+}
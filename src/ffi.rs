@@ -0,0 +1,345 @@
+//! C FFI 接口
+//!
+//! 供 Python（ctypes）、C、Go 等语言以动态库方式调用，仓库根目录的 `layout_view.h` 由 cbindgen 据此生成。
+//! 失败时返回空指针，并在当前线程记录错误：`last_error_code_c` 返回错误码（0 表示没有错误），
+//! `last_error_message_c` 返回错误信息。每次调用成功后清除上一次的错误。
+//! 所有导出函数都在 FFI 边界捕获 panic（panic 不能跨越 C 边界展开），
+//! 转换为 `LayoutError::Panic` 错误，调用方进程不会因为损坏的工作簿而崩溃。
+//! wasm32 目标没有文件系统和 C 调用方，不编译本模块。
+
+use crate::{
+    classify_excel_sheets, classify_excel_sheets_from_bytes, classify_excel_sheets_with_config,
+    extract_form_fields, ClassifierConfig, LayoutError,
+};
+use serde::Serialize;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+thread_local! {
+    static LAST_ERROR: std::cell::RefCell<Option<(i32, String)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// C ABI 版本号：删除或重命名导出函数、修改参数或返回值、修改字符串所有权约定、
+/// 修改已有错误码含义时加 1；新增函数、新增错误码、JSON 结果新增字段不改变版本号
+pub const LAYOUT_VIEW_ABI_VERSION: u32 = 1;
+
+/// C function to get the library version, e.g. "0.1.0"
+/// The returned string is static and must not be freed
+#[no_mangle]
+pub extern "C" fn layout_view_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// C function to get the ABI version of the loaded library
+/// Callers should check it equals the LAYOUT_VIEW_ABI_VERSION they were compiled against
+#[no_mangle]
+pub extern "C" fn layout_view_abi_version() -> u32 {
+    LAYOUT_VIEW_ABI_VERSION
+}
+
+/// C function to classify Excel sheets and return results as JSON string
+/// The caller is responsible for freeing the returned string using free_c_string
+///
+/// # Safety
+/// `xlsx_path` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn classify_excel_sheets_c(xlsx_path: *const c_char) -> *mut c_char {
+    ffi_call(|| unsafe { str_from_c(xlsx_path, "xlsx_path") }.and_then(classify_excel_sheets))
+}
+
+/// C function to classify an in-memory workbook and return results as JSON string
+/// The caller is responsible for freeing the returned string using free_c_string
+///
+/// # Safety
+/// `data` must be null or point to at least `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn classify_excel_sheets_bytes_c(data: *const u8, len: usize) -> *mut c_char {
+    ffi_call(|| {
+        let bytes = unsafe { bytes_from_c(data, len) }?;
+        classify_excel_sheets_from_bytes(bytes, &ClassifierConfig::default())
+    })
+}
+
+/// C function to classify Excel sheets with classifier options given as a JSON string
+/// `options_json` may be null to use the default thresholds; fields left out of the
+/// JSON object keep their default values. Returns null on invalid options.
+/// The caller is responsible for freeing the returned string using free_c_string
+///
+/// # Safety
+/// `xlsx_path` must be null or point to a valid NUL-terminated string, and
+/// `options_json` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn classify_excel_sheets_with_options_c(
+    xlsx_path: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let path = unsafe { str_from_c(xlsx_path, "xlsx_path") }?;
+        let config = unsafe { config_from_c_str(options_json) }?;
+        classify_excel_sheets_with_config(path, &config)
+    })
+}
+
+/// C function to classify an in-memory workbook with classifier options given as a JSON string
+/// The caller is responsible for freeing the returned string using free_c_string
+///
+/// # Safety
+/// `data` must be null or point to at least `len` readable bytes, and
+/// `options_json` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn classify_excel_sheets_bytes_with_options_c(
+    data: *const u8,
+    len: usize,
+    options_json: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let bytes = unsafe { bytes_from_c(data, len) }?;
+        let config = unsafe { config_from_c_str(options_json) }?;
+        classify_excel_sheets_from_bytes(bytes, &config)
+    })
+}
+
+/// 解析 FFI 传入的 JSON 配置，空指针表示使用默认配置
+unsafe fn config_from_c_str(options_json: *const c_char) -> Result<ClassifierConfig, LayoutError> {
+    if options_json.is_null() {
+        return Ok(ClassifierConfig::default());
+    }
+
+    let json = unsafe { str_from_c(options_json, "options_json") }?;
+    ClassifierConfig::from_json_str(json)
+}
+
+/// C function to extract key-value fields from Form sheets and return them as JSON string
+/// The caller is responsible for freeing the returned string using free_c_string
+///
+/// # Safety
+/// `xlsx_path` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn extract_form_fields_c(xlsx_path: *const c_char) -> *mut c_char {
+    ffi_call(|| unsafe { str_from_c(xlsx_path, "xlsx_path") }.and_then(extract_form_fields))
+}
+
+/// 把 FFI 传入的 C 字符串转换为 `&str`，空指针和非 UTF-8 字符串视为无效参数
+unsafe fn str_from_c<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, LayoutError> {
+    if ptr.is_null() {
+        return Err(LayoutError::InvalidArgument(format!("{} is null", name)));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| LayoutError::InvalidArgument(format!("{} is not valid UTF-8", name)))
+}
+
+/// 把 FFI 传入的字节指针转换为切片，空指针视为无效参数
+unsafe fn bytes_from_c<'a>(data: *const u8, len: usize) -> Result<&'a [u8], LayoutError> {
+    if data.is_null() {
+        return Err(LayoutError::InvalidArgument("data is null".to_string()));
+    }
+    Ok(unsafe { std::slice::from_raw_parts(data, len) })
+}
+
+/// 执行 FFI 调用并把结果序列化为 C 字符串：失败或 panic 时记录当前线程的错误并返回空指针，
+/// 成功时清除错误
+fn ffi_call<T: Serialize, F: FnOnce() -> Result<T, LayoutError>>(f: F) -> *mut c_char {
+    let results = catch_panic(f).and_then(|results| Ok(serde_json::to_string(&results)?));
+    // serde_json 会转义 NUL，JSON 字符串中不会出现内部的 NUL
+    match results.map(|json| CString::new(json).unwrap_or_default()) {
+        Ok(c_string) => {
+            set_last_error(None);
+            c_string.into_raw()
+        }
+        Err(e) => {
+            set_last_error(Some(&e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// 捕获 `f` 中的 panic，转换为 `LayoutError::Panic`
+fn catch_panic<T, F: FnOnce() -> Result<T, LayoutError>>(f: F) -> Result<T, LayoutError> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(LayoutError::Panic(message))
+    })
+}
+
+fn set_last_error(error: Option<&LayoutError>) {
+    let error = error.map(|e| (e.code(), e.to_string()));
+    // 线程结束时 thread_local 已销毁，忽略这种情况
+    let _ = LAST_ERROR.try_with(|last| *last.borrow_mut() = error);
+}
+
+/// C function to get the error code of the last failed call on the current thread
+/// Returns 0 if the last call succeeded. Codes: 1 invalid argument, 2 I/O error,
+/// 3 unsupported format, 4 invalid workbook, 5 invalid config, 6 serialization error,
+/// 7 internal error (panic)
+#[no_mangle]
+pub extern "C" fn last_error_code_c() -> i32 {
+    LAST_ERROR
+        .try_with(|last| last.borrow().as_ref().map_or(0, |(code, _)| *code))
+        .unwrap_or(0)
+}
+
+/// C function to get the error message of the last failed call on the current thread
+/// Returns null if the last call succeeded.
+/// The caller is responsible for freeing the returned string using free_c_string
+#[no_mangle]
+pub extern "C" fn last_error_message_c() -> *mut c_char {
+    let message = LAST_ERROR.try_with(|last| last.borrow().as_ref().map(|(_, m)| m.clone()));
+    match message {
+        Ok(Some(message)) => CString::new(message.replace('\0', ""))
+            .unwrap_or_default()
+            .into_raw(),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// C function to free strings allocated by Rust
+///
+/// # Safety
+/// `ptr` must be null or a pointer previously returned by this library,
+/// and must not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn free_c_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        let _ = std::panic::catch_unwind(|| unsafe { CString::from_raw(ptr) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_catches_panic() {
+        let result = ffi_call(|| -> Result<(), LayoutError> { panic!("boom") });
+        assert!(result.is_null());
+        assert_eq!(last_error_code_c(), 7);
+        let message = last_error_message_c();
+        assert_eq!(
+            unsafe { CStr::from_ptr(message) }.to_str().unwrap(),
+            "Internal error: boom"
+        );
+        unsafe { free_c_string(message) };
+
+        // 并行分析的工作线程中的 panic 同样在 FFI 边界被捕获
+        let result = ffi_call(|| {
+            Ok(crate::parallel::parallel_map(
+                &[1, 2, 3, 4],
+                2,
+                |&i: &i32| {
+                    assert_ne!(i, 3, "worker panic");
+                    i
+                },
+            ))
+        });
+        assert!(result.is_null());
+        assert_eq!(last_error_code_c(), 7);
+    }
+
+    #[test]
+    fn test_ffi_malformed_workbooks() {
+        // 对正常工作簿做截断、逐字节篡改，任何输入都只能返回结果或错误，不能使进程崩溃
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize
+        };
+
+        for fixture in ["tests/test1_data.xlsx", "tests/test2_form.xlsx"] {
+            let original = std::fs::read(fixture).unwrap();
+            for round in 0..12 {
+                let mut data = original.clone();
+                if round % 3 == 0 {
+                    data.truncate(next() % data.len());
+                } else {
+                    for _ in 0..1 + next() % 16 {
+                        let pos = next() % data.len();
+                        data[pos] = next() as u8;
+                    }
+                }
+
+                let result = unsafe { classify_excel_sheets_bytes_c(data.as_ptr(), data.len()) };
+                if result.is_null() {
+                    assert!((1..=7).contains(&last_error_code_c()));
+                } else {
+                    assert_eq!(last_error_code_c(), 0);
+                    unsafe { free_c_string(result) };
+                }
+            }
+        }
+
+        let result = unsafe { classify_excel_sheets_bytes_c(std::ptr::null(), 10) };
+        assert!(result.is_null());
+        assert_eq!(last_error_code_c(), 1);
+    }
+
+    #[test]
+    fn test_ffi_last_error() {
+        let message = |ptr: *mut c_char| unsafe {
+            let text = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+            free_c_string(ptr);
+            text
+        };
+
+        let missing = CString::new("tests/does_not_exist.xlsx").unwrap();
+        let result = unsafe { classify_excel_sheets_c(missing.as_ptr()) };
+        assert!(result.is_null());
+        assert_eq!(last_error_code_c(), 2);
+        assert!(message(last_error_message_c()).starts_with("I/O error"));
+
+        let result = unsafe { classify_excel_sheets_c(std::ptr::null()) };
+        assert!(result.is_null());
+        assert_eq!(last_error_code_c(), 1);
+
+        let path = CString::new("tests/test1_form.xlsx").unwrap();
+        let options = CString::new(r#"{"hgih_density": 0.8}"#).unwrap();
+        let result =
+            unsafe { classify_excel_sheets_with_options_c(path.as_ptr(), options.as_ptr()) };
+        assert!(result.is_null());
+        assert_eq!(last_error_code_c(), 5);
+
+        // 成功调用后清除错误
+        let result = unsafe { classify_excel_sheets_c(path.as_ptr()) };
+        assert!(message(result).starts_with('['));
+        assert_eq!(last_error_code_c(), 0);
+        assert!(last_error_message_c().is_null());
+    }
+
+    #[test]
+    fn test_ffi_version() {
+        let version = unsafe { CStr::from_ptr(layout_view_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+        assert_eq!(layout_view_abi_version(), LAYOUT_VIEW_ABI_VERSION);
+    }
+
+    #[test]
+    fn test_c_header_up_to_date() {
+        // 修改导出函数后用 `UPDATE_C_HEADER=1 cargo test c_header` 重新生成 layout_view.h
+        let crate_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+        let bindings = cbindgen::Builder::new()
+            .with_crate(crate_dir)
+            .with_config(config)
+            .generate()
+            .unwrap();
+        let path = crate_dir.join("layout_view.h");
+        if std::env::var_os("UPDATE_C_HEADER").is_some() {
+            bindings.write_to_file(&path);
+        }
+
+        let mut generated = Vec::new();
+        bindings.write(&mut generated);
+        let committed = std::fs::read(&path).unwrap_or_default();
+        assert!(
+            generated == committed,
+            "layout_view.h is out of date, run `UPDATE_C_HEADER=1 cargo test c_header`"
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! 表单键值对提取
+//!
+//! 对分类为 Form 的工作表，把标签单元格与值单元格配对，支持三种排列方式：
+//! - 值在标签右侧（同一行，中间可以隔几列空白，常见于合并单元格）
+//! - 值在标签下方（标签行下面紧跟一行对齐的值）
+//! - 标签与值在同一单元格内，以半角或全角冒号分隔（"姓名：张三"）
+
+use crate::numeric::{is_numeric_cell, NumberLocale};
+use crate::{cell_reference, format_cell, is_empty_cell};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// 标签文本的最大长度，更长的文本视为说明性内容
+const MAX_LABEL_CHARS: usize = 80;
+// 单元格内 "标签：值" 形式中标签部分的最大长度
+const MAX_INLINE_LABEL_CHARS: usize = 30;
+// 标签与右侧值之间最多允许的空白列数
+const MAX_VALUE_GAP: u32 = 3;
+
+/// 一个表单字段
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FormField {
+    pub label: String,
+    pub value: String,
+    pub label_cell: String, // A1 形式的单元格地址
+    pub value_cell: String, // 同一单元格内的键值对与 label_cell 相同
+}
+
+/// 单个工作表的表单字段
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SheetFormFields {
+    pub sheet_name: String,
+    pub fields: Vec<FormField>,
+}
+
+type Cell<'a> = (u32, u32, &'a calamine::Data);
+
+/// 从工作表数据中提取表单字段，按标签所在位置从上到下、从左到右排列
+/// `locale` 为数值字符串的小数点约定（通常取 `ClassifierConfig::number_locale`），数值不会被当作标签
+pub fn extract_form_fields_from_range(
+    range: &calamine::Range<calamine::Data>,
+    locale: NumberLocale,
+) -> Vec<FormField> {
+    let (base_row, base_col) = range.start().unwrap_or((0, 0));
+
+    // 按行收集非空单元格
+    let mut rows: BTreeMap<u32, Vec<Cell>> = BTreeMap::new();
+    for (row, col, cell) in range.used_cells() {
+        if !is_empty_cell(cell) {
+            let (row, col) = (row as u32 + base_row, col as u32 + base_col);
+            rows.entry(row).or_default().push((row, col, cell));
+        }
+    }
+    let cell_at = |row: u32, col: u32| -> Option<&calamine::Data> {
+        rows.get(&row)
+            .and_then(|cells| cells.iter().find(|(_, c, _)| *c == col))
+            .map(|(_, _, cell)| *cell)
+    };
+
+    let vertical_rows = vertical_label_rows(&rows, locale);
+    let label_columns = label_columns(&rows, &vertical_rows, locale);
+
+    let mut fields = Vec::new();
+    let mut consumed: HashSet<(u32, u32)> = HashSet::new();
+    let mut pending: Vec<(u32, u32)> = Vec::new();
+
+    // 同一单元格内的键值对和右侧取值
+    for (row, cells) in &rows {
+        let vertical = vertical_rows.contains(row);
+        let mut i = 0;
+        while i < cells.len() {
+            let (row, col, cell) = cells[i];
+            i += 1;
+            if consumed.contains(&(row, col)) {
+                continue;
+            }
+
+            if let Some((label, value)) = split_inline(cell) {
+                let reference = cell_reference(row, col);
+                fields.push((
+                    (row, col),
+                    FormField {
+                        label,
+                        value,
+                        label_cell: reference.clone(),
+                        value_cell: reference,
+                    },
+                ));
+                consumed.insert((row, col));
+                continue;
+            }
+
+            if !is_label_candidate(cell, locale) {
+                continue;
+            }
+
+            if !vertical {
+                if let Some(&(_, value_col, value)) = cells.get(i) {
+                    let acceptable = value_col - col <= MAX_VALUE_GAP + 1
+                        && !ends_with_colon(value)
+                        && !(label_columns.contains(&value_col) && is_label_like(value, locale));
+                    if acceptable {
+                        fields.push(((row, col), field(row, col, cell, row, value_col, value)));
+                        consumed.insert((row, col));
+                        consumed.insert((row, value_col));
+                        i += 1;
+                        continue;
+                    }
+                }
+            }
+
+            pending.push((row, col));
+        }
+    }
+
+    // 右侧没有值的标签，尝试取正下方的单元格
+    for (row, col) in pending {
+        if consumed.contains(&(row, col)) || consumed.contains(&(row + 1, col)) {
+            continue;
+        }
+        let (Some(label), Some(value)) = (cell_at(row, col), cell_at(row + 1, col)) else {
+            continue;
+        };
+        if ends_with_colon(value) || split_inline(value).is_some() {
+            continue;
+        }
+        if !vertical_rows.contains(&row)
+            && is_label_candidate(value, locale)
+            && label_columns.contains(&col)
+        {
+            continue;
+        }
+
+        fields.push(((row, col), field(row, col, label, row + 1, col, value)));
+        consumed.insert((row, col));
+        consumed.insert((row + 1, col));
+    }
+
+    fields.sort_by_key(|(position, _)| *position);
+    fields.into_iter().map(|(_, field)| field).collect()
+}
+
+fn field(
+    label_row: u32,
+    label_col: u32,
+    label: &calamine::Data,
+    value_row: u32,
+    value_col: u32,
+    value: &calamine::Data,
+) -> FormField {
+    FormField {
+        label: clean_label(&format_cell(label)),
+        value: format_cell(value),
+        label_cell: cell_reference(label_row, label_col),
+        value_cell: cell_reference(value_row, value_col),
+    }
+}
+
+/// 找出 "标签行 + 下一行对齐的值" 形式的行：
+/// 该行至少两个单元格且全部是标签候选，下一行在相同的列上都有值，且下一行至少一半是非文本值
+fn vertical_label_rows(rows: &BTreeMap<u32, Vec<Cell>>, locale: NumberLocale) -> HashSet<u32> {
+    let mut result = HashSet::new();
+    for (row, cells) in rows {
+        if cells.len() < 2
+            || !cells
+                .iter()
+                .all(|&(_, _, cell)| is_label_candidate(cell, locale))
+        {
+            continue;
+        }
+        let Some(below) = rows.get(&(row + 1)) else {
+            continue;
+        };
+        let below_cols: HashSet<u32> = below.iter().map(|&(_, col, _)| col).collect();
+        let aligned = cells.iter().all(|(_, col, _)| below_cols.contains(col));
+        let values = below
+            .iter()
+            .filter(|&&(_, _, cell)| !is_label_candidate(cell, locale))
+            .count();
+        if aligned && values * 2 >= below.len() {
+            result.insert(*row);
+        }
+    }
+    result
+}
+
+/// 统计每列在"标签-值"交替排列中作为标签和值出现的次数，
+/// 作为标签次数更多的列视为标签列，其中的文本不会被当作左侧标签的值
+fn label_columns(
+    rows: &BTreeMap<u32, Vec<Cell>>,
+    vertical_rows: &HashSet<u32>,
+    locale: NumberLocale,
+) -> HashSet<u32> {
+    let mut label_votes: HashMap<u32, u32> = HashMap::new();
+    let mut value_votes: HashMap<u32, u32> = HashMap::new();
+
+    for (row, cells) in rows {
+        if vertical_rows.contains(row) || vertical_rows.contains(&row.wrapping_sub(1)) {
+            continue;
+        }
+        let mut i = 0;
+        while i < cells.len() {
+            let (_, col, cell) = cells[i];
+            i += 1;
+            if split_inline(cell).is_some() || !is_label_candidate(cell, locale) {
+                continue;
+            }
+            if let Some(&(_, value_col, value)) = cells.get(i) {
+                if value_col - col <= MAX_VALUE_GAP + 1 && !ends_with_colon(value) {
+                    *label_votes.entry(col).or_default() += 1;
+                    *value_votes.entry(value_col).or_default() += 1;
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    label_votes
+        .into_iter()
+        .filter(|(col, votes)| *votes > value_votes.get(col).copied().unwrap_or(0))
+        .map(|(col, _)| col)
+        .collect()
+}
+
+/// 标签候选：长度适中的非数值文本
+fn is_label_candidate(cell: &calamine::Data, locale: NumberLocale) -> bool {
+    match cell {
+        calamine::Data::String(s) => {
+            let s = s.trim();
+            !s.is_empty() && s.chars().count() <= MAX_LABEL_CHARS && !is_numeric_cell(cell, locale)
+        }
+        _ => false,
+    }
+}
+
+/// 标签列中的文本是否应视为标签：含数字的文本（地址、编号等）仍视为值
+fn is_label_like(cell: &calamine::Data, locale: NumberLocale) -> bool {
+    match cell {
+        calamine::Data::String(s) => {
+            is_label_candidate(cell, locale) && !s.chars().any(|c| c.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
+
+fn ends_with_colon(cell: &calamine::Data) -> bool {
+    match cell {
+        calamine::Data::String(s) => {
+            let s = s.trim_end();
+            s.ends_with(':') || s.ends_with('：')
+        }
+        _ => false,
+    }
+}
+
+/// 去掉标签末尾的冒号和空白
+fn clean_label(label: &str) -> String {
+    label
+        .trim()
+        .trim_end_matches([':', '：'])
+        .trim_end()
+        .to_string()
+}
+
+/// 拆分 "标签：值" 形式的单元格，排除时间（12:30）、URL 以及括号内的冒号
+fn split_inline(cell: &calamine::Data) -> Option<(String, String)> {
+    let calamine::Data::String(s) = cell else {
+        return None;
+    };
+    let s = s.trim();
+    let (pos, colon) = s.char_indices().find(|&(_, c)| c == ':' || c == '：')?;
+    let label = s[..pos].trim();
+    let value = s[pos + colon.len_utf8()..].trim();
+
+    if label.is_empty() || value.is_empty() || label.chars().count() > MAX_INLINE_LABEL_CHARS {
+        return None;
+    }
+    if !label.chars().any(|c| c.is_alphabetic()) || value.starts_with("//") {
+        return None;
+    }
+    let open = label.chars().filter(|&c| c == '(' || c == '（').count();
+    let close = label.chars().filter(|&c| c == ')' || c == '）').count();
+    if open != close {
+        return None;
+    }
+
+    Some((label.to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Data, Range};
+
+    fn text(s: &str) -> Data {
+        Data::String(s.to_string())
+    }
+
+    #[test]
+    fn test_split_inline() {
+        assert_eq!(
+            split_inline(&text("姓名：张三")),
+            Some(("姓名".to_string(), "张三".to_string()))
+        );
+        assert_eq!(
+            split_inline(&text("Name: John")),
+            Some(("Name".to_string(), "John".to_string()))
+        );
+        assert_eq!(split_inline(&text("12:30")), None);
+        assert_eq!(split_inline(&text("http://example.com")), None);
+        assert_eq!(split_inline(&text("地址（邮编：200032）")), None);
+        assert_eq!(split_inline(&text("备注：")), None);
+    }
+
+    #[test]
+    fn test_extract_form_fields() {
+        let mut range = Range::new((0, 0), (5, 3));
+        range.set_value((0, 0), text("员工信息表"));
+        range.set_value((1, 0), text("姓名"));
+        range.set_value((1, 1), text("张三"));
+        range.set_value((1, 2), text("部门："));
+        range.set_value((1, 3), text("财务部"));
+        range.set_value((2, 0), text("电话：13800000000"));
+        range.set_value((2, 2), text("职位"));
+        // 值在下方的标签行
+        range.set_value((4, 0), text("入职年份"));
+        range.set_value((4, 1), text("工号"));
+        range.set_value((5, 0), Data::Int(2024));
+        range.set_value((5, 1), Data::Int(1001));
+
+        let fields = extract_form_fields_from_range(&range, NumberLocale::DecimalPoint);
+        let pairs: Vec<(&str, &str, &str, &str)> = fields
+            .iter()
+            .map(|f| {
+                (
+                    f.label.as_str(),
+                    f.value.as_str(),
+                    f.label_cell.as_str(),
+                    f.value_cell.as_str(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            pairs,
+            vec![
+                ("姓名", "张三", "A2", "B2"),
+                ("部门", "财务部", "C2", "D2"),
+                ("电话", "13800000000", "A3", "A3"),
+                ("入职年份", "2024", "A5", "A6"),
+                ("工号", "1001", "B5", "B6"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_form_fields_with_locale() {
+        // 小数逗号约定下 "1.234,56" 是数值，标签行下方的值按列配对；小数点约定下它是文本标签
+        let mut range = Range::new((0, 0), (1, 1));
+        range.set_value((0, 0), text("单价"));
+        range.set_value((0, 1), text("数量"));
+        range.set_value((1, 0), text("1.234,56"));
+        range.set_value((1, 1), text("12,5"));
+
+        let pairs = |locale| {
+            extract_form_fields_from_range(&range, locale)
+                .into_iter()
+                .map(|f| (f.label, f.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            pairs(NumberLocale::DecimalComma),
+            vec![
+                ("单价".to_string(), "1.234,56".to_string()),
+                ("数量".to_string(), "12,5".to_string()),
+            ]
+        );
+        assert_ne!(
+            pairs(NumberLocale::DecimalPoint),
+            pairs(NumberLocale::DecimalComma)
+        );
+    }
+}
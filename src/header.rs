@@ -0,0 +1,465 @@
+//! 表头行识别
+//!
+//! 表头通常是一行几乎全为文本的单元格，覆盖了下方数据所在的大部分列，
+//! 而下方数据行中的数值、日期、编号等取值型单元格明显更多。据此对前若干行逐行打分，取得分最高的一行。
+//!
+//! 得分最高的一行之上如果紧接着分组标题行（如横向合并、覆盖 Q1-Q4 的 "2024"），
+//! 一并作为多层表头，列名由各层标题以 `/` 连接（"2024/Q1"），同时给出表头的层级结构。
+
+use crate::datatype::{cell_category, CellCategory};
+use crate::numeric::{is_code_string, NumberLocale};
+use crate::shape::is_period_label;
+use crate::{is_empty_cell, MergedRegion};
+use serde::{Deserialize, Serialize};
+
+// 只在数据块的前若干行中寻找表头
+const MAX_HEADER_SCAN_ROWS: u32 = 10;
+// 计算列类型对比时使用的表头下方行数
+const BODY_SAMPLE_ROWS: u32 = 50;
+// 表头得分低于该值时认为没有表头
+const MIN_HEADER_SCORE: f64 = 0.5;
+// 多层表头最多的层数
+const MAX_HEADER_LEVELS: u32 = 3;
+// 多层表头中各层标题的连接符
+const LEVEL_SEPARATOR: &str = "/";
+
+/// 识别出的表头
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HeaderInfo {
+    pub(crate) row: u32,       // 最下面一层表头所在行，数据从下一行开始
+    pub(crate) rows: Vec<u32>, // 多层表头的所有行，从上到下
+    pub(crate) column_names: Vec<String>,
+    pub(crate) tree: Vec<HeaderNode>,
+}
+
+/// 多层表头中的一个标题，`first_col`..=`end_col` 为它覆盖的列
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HeaderNode {
+    pub label: String,
+    pub row: u32,
+    pub first_col: u32,
+    pub end_col: u32,
+    #[serde(default)]
+    pub children: Vec<HeaderNode>,
+}
+
+/// 在给定范围内识别表头行（包括其上的分组标题行），并生成每列的列名
+pub(crate) fn detect_header(
+    range: &calamine::Range<calamine::Data>,
+    merged_regions: &[MergedRegion],
+    (start_row, start_col, end_row, end_col): (u32, u32, u32, u32),
+    locale: NumberLocale,
+) -> Option<HeaderInfo> {
+    if start_row >= end_row {
+        // 只有一行时无法区分表头和数据
+        return None;
+    }
+
+    let last_candidate = std::cmp::min(end_row - 1, start_row + MAX_HEADER_SCAN_ROWS - 1);
+    let mut best: Option<(u32, f64)> = None;
+
+    for row in start_row..=last_candidate {
+        let score = header_score(range, row, start_col, end_row, end_col, locale);
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((row, score));
+        }
+    }
+
+    let (row, score) = best?;
+    if score < MIN_HEADER_SCORE {
+        return None;
+    }
+
+    // 向上合并紧接着的分组标题行
+    let mut rows = vec![row];
+    while rows.len() < MAX_HEADER_LEVELS as usize {
+        let upper = rows[0];
+        if upper == start_row
+            || !is_group_row(
+                range,
+                merged_regions,
+                upper - 1,
+                upper,
+                (start_col, end_col),
+                locale,
+            )
+        {
+            break;
+        }
+        rows.insert(0, upper - 1);
+    }
+
+    let labels = level_labels(range, merged_regions, &rows, start_col, end_col);
+    Some(HeaderInfo {
+        row,
+        column_names: column_names(&labels, start_col),
+        tree: build_tree(&labels, &rows, 0, start_col, start_col, end_col),
+        rows,
+    })
+}
+
+/// 分组标题行：至少两个非空单元格，且全部是标签（文本或年份、季度等期间）；
+/// 下方有标题的每个分组标题都要横跨下一层的多列（横向合并或沿用左侧标题），
+/// 下方为空的是纵向合并的列标题。标题加 "单位：元" 这类说明的行不是分组标题行。
+fn is_group_row(
+    range: &calamine::Range<calamine::Data>,
+    merged_regions: &[MergedRegion],
+    row: u32,
+    lower_row: u32,
+    (start_col, end_col): (u32, u32),
+    locale: NumberLocale,
+) -> bool {
+    let cells: Vec<&calamine::Data> = (start_col..=end_col)
+        .filter_map(|col| range.get_value((row, col)))
+        .filter(|cell| !is_empty_cell(cell))
+        .collect();
+    let all_labels = cells
+        .iter()
+        .all(|cell| !is_value_cell(cell, locale) || is_period_label(&cell.to_string()));
+    if cells.len() < 2 || !all_labels {
+        return false;
+    }
+
+    let labels = level_labels(range, merged_regions, &[row, lower_row], start_col, end_col);
+    let (upper, lower) = (&labels[0], &labels[1]);
+    let mut groups = 0;
+    let mut first = 0;
+    while first < upper.len() {
+        let mut end = first;
+        while end + 1 < upper.len() && upper[end + 1] == upper[first] {
+            end += 1;
+        }
+        let children = lower[first..=end].iter().filter(|l| l.is_some()).count();
+        if upper[first].is_some() && children > 0 {
+            if children < 2 {
+                return false;
+            }
+            groups += 1;
+        }
+        first = end + 1;
+    }
+    groups > 0
+}
+
+/// 每层表头中每列的标题，层数与 `rows` 一致
+/// 上层空白单元格在横向合并区域内时取合并区域左上角的标题；工作表没有合并信息时，
+/// 在同一个上级标题下沿用左侧的分组标题（下一层有标题的才是分组，下面为空的是纵向合并的列标题）。
+/// 最下层的空白单元格保持空白。
+pub(crate) fn level_labels(
+    range: &calamine::Range<calamine::Data>,
+    merged_regions: &[MergedRegion],
+    rows: &[u32],
+    start_col: u32,
+    end_col: u32,
+) -> Vec<Vec<Option<String>>> {
+    let text = |row: u32, col: u32| {
+        range
+            .get_value((row, col))
+            .filter(|cell| !is_empty_cell(cell))
+            .map(|cell| cell.to_string().trim().to_string())
+    };
+
+    let mut levels: Vec<Vec<Option<String>>> = Vec::with_capacity(rows.len());
+    for (level, &row) in rows.iter().enumerate() {
+        let is_last = level + 1 == rows.len();
+        let mut labels: Vec<Option<String>> = Vec::new();
+        let mut group_open = false;
+        for col in start_col..=end_col {
+            let offset = (col - start_col) as usize;
+            let own = text(row, col);
+            if own.is_some() {
+                group_open = !is_last && text(rows[level + 1], col).is_some();
+            }
+            let label = own.or_else(|| {
+                // 横向合并的分组标题取左上角；纵向合并的标题只属于最上面一层
+                if let Some(region) = merged_regions.iter().find(|r| r.contains(row, col)) {
+                    return (region.first_row == row)
+                        .then(|| text(region.first_row, region.first_col))
+                        .flatten();
+                }
+                // 没有合并信息时，在同一个上级标题下沿用左侧的标题
+                let same_parent = levels
+                    .iter()
+                    .all(|upper| offset > 0 && upper[offset] == upper[offset - 1]);
+                if is_last || !merged_regions.is_empty() || !same_parent || !group_open {
+                    return None;
+                }
+                labels.last().cloned().flatten()
+            });
+            labels.push(label);
+        }
+        levels.push(labels);
+    }
+    levels
+}
+
+/// 按层级把相邻且标题相同的列合并为一个节点；某层标题为空的列直接挂下一层的节点
+pub(crate) fn build_tree(
+    labels: &[Vec<Option<String>>],
+    rows: &[u32],
+    level: usize,
+    start_col: u32,
+    first_col: u32,
+    end_col: u32,
+) -> Vec<HeaderNode> {
+    if level >= labels.len() {
+        return Vec::new();
+    }
+    let is_last = level + 1 == labels.len();
+    let label_at = |col: u32| &labels[level][(col - start_col) as usize];
+
+    let mut nodes = Vec::new();
+    let mut col = first_col;
+    while col <= end_col {
+        // 最下层每列单独成为一个节点
+        let mut group_end = col;
+        while !is_last && group_end < end_col && label_at(group_end + 1) == label_at(col) {
+            group_end += 1;
+        }
+        let children = build_tree(labels, rows, level + 1, start_col, col, group_end);
+        match label_at(col) {
+            Some(label) => nodes.push(HeaderNode {
+                label: label.clone(),
+                row: rows[level],
+                first_col: col,
+                end_col: group_end,
+                children,
+            }),
+            None => nodes.extend(children),
+        }
+        col = group_end + 1;
+    }
+    nodes
+}
+
+/// 表头得分 = 列覆盖率 × 文本占比 × (0.5 + 0.5 × 下方取值占比)
+/// - 列覆盖率：下方有数据的列中，表头单元格非空的比例
+/// - 文本占比：表头行非空单元格中非取值型单元格的比例
+/// - 下方取值占比：表头非空的列在下方数据中数值、日期时间和编号单元格的平均占比，体现文本与取值的对比
+fn header_score(
+    range: &calamine::Range<calamine::Data>,
+    row: u32,
+    start_col: u32,
+    end_row: u32,
+    end_col: u32,
+    locale: NumberLocale,
+) -> f64 {
+    let body_end = std::cmp::min(end_row, row + BODY_SAMPLE_ROWS);
+
+    let mut body_columns = 0;
+    let mut covered_columns = 0;
+    let mut header_cells = 0;
+    let mut header_text_cells = 0;
+    let mut contrast_sum = 0.0;
+
+    for col in start_col..=end_col {
+        let mut body_total = 0;
+        let mut body_numeric = 0;
+        for body_row in row + 1..=body_end {
+            if let Some(cell) = range.get_value((body_row, col)) {
+                if !is_empty_cell(cell) {
+                    body_total += 1;
+                    if is_value_cell(cell, locale) {
+                        body_numeric += 1;
+                    }
+                }
+            }
+        }
+
+        let header_cell = range.get_value((row, col)).filter(|c| !is_empty_cell(c));
+        if let Some(cell) = header_cell {
+            header_cells += 1;
+            if !is_value_cell(cell, locale) {
+                header_text_cells += 1;
+            }
+        }
+
+        if body_total == 0 {
+            continue;
+        }
+        body_columns += 1;
+        if header_cell.is_some() {
+            covered_columns += 1;
+            contrast_sum += body_numeric as f64 / body_total as f64;
+        }
+    }
+
+    if body_columns == 0 || covered_columns == 0 {
+        return 0.0;
+    }
+
+    let coverage = covered_columns as f64 / body_columns as f64;
+    let text_ratio = header_text_cells as f64 / header_cells as f64;
+    let contrast = contrast_sum / covered_columns as f64;
+
+    coverage * text_ratio * (0.5 + 0.5 * contrast)
+}
+
+/// 取值型单元格：数值、日期时间，以及以字符串保存的编号（手机号、身份证号等）
+fn is_value_cell(cell: &calamine::Data, locale: NumberLocale) -> bool {
+    match cell_category(cell, locale) {
+        Some(
+            CellCategory::Numeric
+            | CellCategory::Date
+            | CellCategory::DateTime
+            | CellCategory::Time
+            | CellCategory::Duration,
+        ) => true,
+        _ => matches!(cell, calamine::Data::String(s) if is_code_string(s)),
+    }
+}
+
+/// 生成列名：由上到下连接各层标题（相邻重复的标题只保留一次），
+/// 空白列使用 `column_{列号}`，重复列名追加序号
+fn column_names(labels: &[Vec<Option<String>>], start_col: u32) -> Vec<String> {
+    let columns = labels.first().map_or(0, Vec::len);
+    let raw = (0..columns).map(|offset| {
+        let mut parts: Vec<&str> = labels
+            .iter()
+            .filter_map(|level| level[offset].as_deref())
+            .collect();
+        parts.dedup();
+        if parts.is_empty() {
+            format!("column_{}", start_col + offset as u32)
+        } else {
+            parts.join(LEVEL_SEPARATOR)
+        }
+    });
+    dedup_names(raw)
+}
+
+/// 对重复的列名追加 `_2`、`_3` 等后缀，保证列名唯一
+pub(crate) fn dedup_names<I: IntoIterator<Item = String>>(names: I) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut result = Vec::new();
+    for name in names {
+        let mut unique = name.clone();
+        let mut suffix = 2;
+        while !seen.insert(unique.clone()) {
+            unique = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        result.push(unique);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Data, Range};
+
+    fn text(s: &str) -> Data {
+        Data::String(s.to_string())
+    }
+
+    #[test]
+    fn test_detect_header_below_title() {
+        let mut range = Range::new((0, 0), (5, 2));
+        range.set_value((0, 0), text("Sales Report"));
+        range.set_value((1, 0), text("Name"));
+        range.set_value((1, 1), text("Qty"));
+        range.set_value((1, 2), text("Qty"));
+        for row in 2..=5 {
+            range.set_value((row, 0), text("item"));
+            range.set_value((row, 1), Data::Int(row as i64));
+            range.set_value((row, 2), Data::Float(1.5));
+        }
+
+        let header = detect_header(&range, &[], (0, 0, 5, 2), NumberLocale::Auto).unwrap();
+        assert_eq!(header.row, 1);
+        assert_eq!(header.rows, vec![1]);
+        assert_eq!(header.column_names, vec!["Name", "Qty", "Qty_2"]);
+    }
+
+    #[test]
+    fn test_detect_header_none_for_numbers() {
+        let mut range = Range::new((0, 0), (3, 1));
+        for row in 0..=3 {
+            range.set_value((row, 0), Data::Int(1));
+            range.set_value((row, 1), Data::Int(2));
+        }
+
+        assert_eq!(
+            detect_header(&range, &[], (0, 0, 3, 1), NumberLocale::Auto),
+            None
+        );
+    }
+
+    #[test]
+    fn test_title_and_unit_row_not_stacked() {
+        // 标题加 "单位：元" 的说明行不是分组标题行，不论是否有合并信息
+        let mut range = Range::new((0, 0), (4, 3));
+        range.set_value((0, 0), text("2024年销售统计"));
+        range.set_value((0, 3), text("单位：元"));
+        for (col, label) in ["地区", "产品", "数量", "金额"].iter().enumerate() {
+            range.set_value((1, col as u32), text(label));
+        }
+        for row in 2..=4 {
+            range.set_value((row, 0), text("华东"));
+            range.set_value((row, 1), text("A"));
+            range.set_value((row, 2), Data::Int(3));
+            range.set_value((row, 3), Data::Float(1.5));
+        }
+        let title = MergedRegion {
+            first_row: 0,
+            first_col: 0,
+            end_row: 0,
+            end_col: 2,
+        };
+
+        for regions in [&[title][..], &[]] {
+            let header = detect_header(&range, regions, (0, 0, 4, 3), NumberLocale::Auto).unwrap();
+            assert_eq!(header.rows, vec![1]);
+            assert_eq!(header.column_names, vec!["地区", "产品", "数量", "金额"]);
+        }
+    }
+
+    #[test]
+    fn test_detect_stacked_header() {
+        // 第 0 行为年份分组（2024、2025 各覆盖 Q1、Q2），"地区" 纵向合并两行
+        let mut range = Range::new((0, 0), (4, 4));
+        range.set_value((0, 0), text("地区"));
+        range.set_value((0, 1), Data::Int(2024));
+        range.set_value((0, 3), Data::Int(2025));
+        range.set_value((1, 1), text("Q1"));
+        range.set_value((1, 2), text("Q2"));
+        range.set_value((1, 3), text("Q1"));
+        range.set_value((1, 4), text("Q2"));
+        for row in 2..=4 {
+            range.set_value((row, 0), text("华东"));
+            for col in 1..=4 {
+                range.set_value((row, col), Data::Float(1.5));
+            }
+        }
+        let merged = |first_row, first_col, end_row, end_col| MergedRegion {
+            first_row,
+            first_col,
+            end_row,
+            end_col,
+        };
+        let merged_regions = [merged(0, 0, 1, 0), merged(0, 1, 0, 2), merged(0, 3, 0, 4)];
+
+        for regions in [&merged_regions[..], &[]] {
+            let header = detect_header(&range, regions, (0, 0, 4, 4), NumberLocale::Auto).unwrap();
+            assert_eq!(header.row, 1);
+            assert_eq!(header.rows, vec![0, 1]);
+            assert_eq!(
+                header.column_names,
+                vec!["地区", "2024/Q1", "2024/Q2", "2025/Q1", "2025/Q2"]
+            );
+
+            let tree = &header.tree;
+            assert_eq!(tree.len(), 3);
+            assert_eq!(
+                (tree[0].label.as_str(), tree[0].children.len()),
+                ("地区", 0)
+            );
+            assert_eq!(tree[1].label, "2024");
+            assert_eq!((tree[1].first_col, tree[1].end_col), (1, 2));
+            assert_eq!(tree[1].children.len(), 2);
+            assert_eq!(tree[1].children[1].label, "Q2");
+            assert_eq!(tree[2].children[0].row, 1);
+        }
+    }
+}
@@ -1,10 +1,64 @@
-use calamine::{open_workbook, Reader, Xlsx};
-use lazy_static::lazy_static;
-use regex::Regex;
+#[cfg(not(target_arch = "wasm32"))]
+use calamine::{open_workbook_from_rs, Ods, Reader, Sheets, Xls, Xlsb, Xlsx};
 use serde::{Deserialize, Serialize};
-use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
-// use libc;
+use std::collections::BTreeMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::BufReader;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod batch;
+mod config;
+mod crosstab;
+mod datatype;
+mod error;
+mod extract;
+#[cfg(not(target_arch = "wasm32"))]
+mod ffi;
+mod form;
+mod header;
+mod merge;
+mod numeric;
+mod parallel;
+#[cfg(feature = "python")]
+mod python;
+mod region;
+mod rules;
+mod sampling;
+mod semantic;
+mod shape;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use batch::{
+    classify_workbooks_with_config, expand_workbook_paths, is_glob_pattern, WorkbookResult,
+};
+pub use config::{ClassifierConfig, SheetVisibility};
+pub use crosstab::{unpivot_range, CrosstabLayout, UnpivotIter};
+use datatype::CellCategory;
+pub use error::LayoutError;
+pub use extract::{records_from_range, Record, RecordIter};
+#[cfg(not(target_arch = "wasm32"))]
+pub use ffi::{
+    classify_excel_sheets_bytes_c, classify_excel_sheets_bytes_with_options_c,
+    classify_excel_sheets_c, classify_excel_sheets_with_options_c, extract_form_fields_c,
+    free_c_string, last_error_code_c, last_error_message_c, layout_view_abi_version,
+    layout_view_version, LAYOUT_VIEW_ABI_VERSION,
+};
+pub use form::{extract_form_fields_from_range, FormField, SheetFormFields};
+pub use header::HeaderNode;
+pub use merge::{DensityMode, MergedRegion};
+pub use numeric::NumberLocale;
+use rules::{classify_layout, LayoutClassification, LayoutFeatures};
+pub use rules::{ClassificationExplanation, NearMissRule, RuleCondition};
+pub use sampling::{SamplingConfig, SamplingStrategy};
+pub use semantic::SemanticType;
+pub use shape::ShapeFeatures;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SheetDataDensity {
@@ -21,26 +75,155 @@ pub struct SheetDataDensity {
     pub last_row_first_col_content: Option<String>,  // 采样最后一行第一列cell内容
     pub data_type_mix: f64,                          // 数据类型混合程度
     pub column_data_types: Vec<ColumnDataTypeInfo>,  // 每列的数据类型信息
+    #[serde(default)]
+    pub error_ratio: f64,       // 错误值单元格占数据单元格的比例
+    #[serde(default)]
+    pub header_row: Option<u32>, // 识别出的表头行
+    #[serde(default)]
+    pub column_names: Vec<String>, // 与 column_data_types 一一对应的列名
+    #[serde(default)]
+    pub header_rows: Vec<u32>,  // 多层表头的所有行（从上到下），单层表头时只有 header_row
+    #[serde(default)]
+    pub header_tree: Vec<HeaderNode>, // 表头的层级结构
     pub row_type_consistency: f64,                   // 行间类型一致性（0-1，越高越一致）
-    pub aspect_ratio: f64,                           // 宽高比（行数/列数）
+    pub aspect_ratio: f64,                           // 宽高比（采样行数/列数）
+    #[serde(default)]
+    pub sampling_strategy: SamplingStrategy, // 采样策略
+    #[serde(default)]
+    pub sampled_rows: u32,      // 实际参与计算的行数
+    #[serde(default)]
+    pub merged_cells: u32,      // 采样行中被合并区域覆盖的单元格数
+    #[serde(default)]
+    pub merged_cell_ratio: f64, // 被合并区域覆盖的单元格占比
+    #[serde(default)]
+    pub cell_density: f64,      // 只按非空单元格计算的密度，不受 density_mode 影响
+    #[serde(default)]
+    pub shape_features: ShapeFeatures, // 区分细分布局类型的特征
+    #[serde(default)]
+    pub crosstab: Option<CrosstabLayout>, // 交叉表结构（行标签列、列标签行和数值表体）
+    #[serde(default)]
+    pub merged_regions: Vec<MergedRegion>, // 工作表中的合并区域
+    #[serde(default)]
+    pub regions: Vec<SheetRegion>, // 由空行/空列分隔的数据块
 }
 
+/// 工作表中由整行/整列空白分隔出的独立数据块
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SheetRegion {
+    pub region_index: u32,
+    pub first_row: u32,
+    pub first_col: u32,
+    pub end_row: u32,
+    pub end_col: u32,
+    pub total_cells: u32,
+    pub data_cells: u32,
+    pub density: f64,
+    pub first_row_first_col_content: Option<String>,
+    pub last_row_first_col_content: Option<String>,
+    pub data_type_mix: f64,
+    pub column_data_types: Vec<ColumnDataTypeInfo>,
+    #[serde(default)]
+    pub error_ratio: f64,
+    pub header_row: Option<u32>,
+    pub column_names: Vec<String>,
+    #[serde(default)]
+    pub header_rows: Vec<u32>,
+    #[serde(default)]
+    pub header_tree: Vec<HeaderNode>,
+    pub row_type_consistency: f64,
+    pub aspect_ratio: f64,
+    #[serde(default)]
+    pub sampling_strategy: SamplingStrategy,
+    #[serde(default)]
+    pub sampled_rows: u32,
+    #[serde(default)]
+    pub merged_cells: u32,
+    #[serde(default)]
+    pub merged_cell_ratio: f64,
+    #[serde(default)]
+    pub cell_density: f64,
+    #[serde(default)]
+    pub shape_features: ShapeFeatures,
+    #[serde(default)]
+    pub crosstab: Option<CrosstabLayout>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ColumnDataTypeInfo {
     pub column_index: u32,
     pub numeric_count: u32,
     pub text_count: u32,
+    #[serde(default)]
+    pub date_count: u32, // 日期（不含时间）
+    #[serde(default)]
+    pub datetime_count: u32, // 日期时间
+    #[serde(default)]
+    pub time_count: u32, // 时间（不含日期）
+    #[serde(default)]
+    pub duration_count: u32, // 时长
+    #[serde(default)]
+    pub bool_count: u32, // 布尔值
+    #[serde(default)]
+    pub error_count: u32, // 错误值（#N/A、#REF! 等）
     pub total_count: u32,
     pub numeric_type_ratio: f64, // 数值型数据占比
+    #[serde(default)]
+    pub date_type_ratio: f64,
+    #[serde(default)]
+    pub datetime_type_ratio: f64,
+    #[serde(default)]
+    pub time_type_ratio: f64,
+    #[serde(default)]
+    pub duration_type_ratio: f64,
+    #[serde(default)]
+    pub bool_type_ratio: f64,
+    #[serde(default)]
+    pub error_type_ratio: f64,
+    #[serde(default)]
+    pub error_kinds: BTreeMap<String, u32>, // 错误类型 -> 出现次数
+    #[serde(default)]
+    pub semantic_type: SemanticType, // 列的语义类型（编号、手机号、金额等），不含表头行
+    #[serde(default)]
+    pub semantic_ratio: f64, // 符合该语义类型的单元格占比
+    #[serde(default)]
+    pub semantic_matches: BTreeMap<SemanticType, f64>, // 各语义类型的匹配占比
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+impl ColumnDataTypeInfo {
+    /// 各类别的单元格数，顺序为数值、文本、日期、日期时间、时间、时长、布尔、错误
+    fn category_counts(&self) -> [u32; 8] {
+        [
+            self.numeric_count,
+            self.text_count,
+            self.date_count,
+            self.datetime_count,
+            self.time_count,
+            self.duration_count,
+            self.bool_count,
+            self.error_count,
+        ]
+    }
+}
+
+/// 工作表类型
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub enum SheetType {
-    Data,    // 行列表
-    Form,    // 表单
+    Data, // 行列表
+    Form, // 表单
+    #[default]
     Unknown, // 无法确定
 }
 
+/// 细分布局类型，在 `SheetType` 的基础上进一步区分
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutType {
+    Crosstab, // 交叉表（行标签 × 期间等列标签）
+    List,     // 单列清单
+    Lookup,   // 代码对照表
+    Calendar, // 日历
+    Notes,    // 说明文字
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ClassifiedSheet {
     pub sheet_name: String,
@@ -56,137 +239,587 @@ pub struct ClassifiedSheet {
     pub last_row_first_col_content: Option<String>,  // 采样最后一行第一列cell内容
     pub data_type_mix: f64,                          // 数据类型混合程度
     pub column_data_types: Vec<ColumnDataTypeInfo>,  // 每列的数据类型信息
+    #[serde(default)]
+    pub error_ratio: f64,       // 错误值单元格占数据单元格的比例
+    #[serde(default)]
+    pub header_row: Option<u32>, // 表头行（仅 Data 类型）
+    #[serde(default)]
+    pub column_names: Vec<String>, // 列名（仅 Data 类型）
+    #[serde(default)]
+    pub header_rows: Vec<u32>,  // 多层表头的所有行（仅 Data 类型）
+    #[serde(default)]
+    pub header_tree: Vec<HeaderNode>, // 表头的层级结构（仅 Data 类型）
     pub row_type_consistency: f64,                   // 行间类型一致性（0-1，越高越一致）
-    pub aspect_ratio: f64,                           // 宽高比（行数/列数）
+    pub aspect_ratio: f64,                           // 宽高比（采样行数/列数）
+    #[serde(default)]
+    pub sampling_strategy: SamplingStrategy, // 采样策略
+    #[serde(default)]
+    pub sampled_rows: u32,      // 实际参与计算的行数
+    #[serde(default)]
+    pub merged_cells: u32,      // 采样行中被合并区域覆盖的单元格数
+    #[serde(default)]
+    pub merged_cell_ratio: f64, // 被合并区域覆盖的单元格占比
+    #[serde(default)]
+    pub cell_density: f64,      // 只按非空单元格计算的密度，不受 density_mode 影响
+    #[serde(default)]
+    pub shape_features: ShapeFeatures, // 区分细分布局类型的特征
+    #[serde(default)]
+    pub crosstab: Option<CrosstabLayout>, // 交叉表结构（行标签列、列标签行和数值表体）
+    #[serde(default)]
+    pub merged_regions: Vec<MergedRegion>, // 工作表中的合并区域
     pub sheet_type: SheetType,
+    #[serde(default)]
+    pub layout_type: Option<LayoutType>, // 细分布局类型，没有命中细分规则时为 None
     pub classification_reason: String, // 分类原因说明
+    #[serde(default)]
+    pub confidence: f64, // 分类置信度（0-1，越接近0越处于规则边界）
+    #[serde(default)]
+    pub explanation: ClassificationExplanation, // 命中的规则、阈值及接近命中的规则
+    #[serde(default)]
+    pub regions: Vec<ClassifiedRegion>, // 各数据块的分类结果
+}
+
+/// 单个数据块的分类结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClassifiedRegion {
+    pub region_index: u32,
+    pub first_row: u32,
+    pub first_col: u32,
+    pub end_row: u32,
+    pub end_col: u32,
+    pub total_cells: u32,
+    pub data_cells: u32,
+    pub density: f64,
+    pub first_row_first_col_content: Option<String>,
+    pub last_row_first_col_content: Option<String>,
+    pub data_type_mix: f64,
+    pub column_data_types: Vec<ColumnDataTypeInfo>,
+    #[serde(default)]
+    pub error_ratio: f64,
+    pub header_row: Option<u32>,
+    pub column_names: Vec<String>,
+    #[serde(default)]
+    pub header_rows: Vec<u32>,
+    #[serde(default)]
+    pub header_tree: Vec<HeaderNode>,
+    pub row_type_consistency: f64,
+    pub aspect_ratio: f64,
+    #[serde(default)]
+    pub sampling_strategy: SamplingStrategy,
+    #[serde(default)]
+    pub sampled_rows: u32,
+    #[serde(default)]
+    pub merged_cells: u32,
+    #[serde(default)]
+    pub merged_cell_ratio: f64,
+    #[serde(default)]
+    pub cell_density: f64,
+    #[serde(default)]
+    pub shape_features: ShapeFeatures,
+    #[serde(default)]
+    pub crosstab: Option<CrosstabLayout>,
+    pub sheet_type: SheetType,
+    #[serde(default)]
+    pub layout_type: Option<LayoutType>,
+    pub classification_reason: String,
+    pub confidence: f64,
+    pub explanation: ClassificationExplanation,
+}
+
+/// 计算工作簿中每个工作表的数据密度
+/// 通过扩展名和文件头自动识别格式，支持 .xlsx/.xlsm/.xlsb/.xls/.ods
+#[cfg(not(target_arch = "wasm32"))]
+pub fn calculate_sheet_density(xlsx_path: &str) -> Result<Vec<SheetDataDensity>, LayoutError> {
+    calculate_sheet_density_with_config(xlsx_path, &ClassifierConfig::default())
 }
 
-pub fn calculate_sheet_density(
+/// 按配置中的采样方式和可见性计算数据密度
+#[cfg(not(target_arch = "wasm32"))]
+pub fn calculate_sheet_density_with_config(
     xlsx_path: &str,
-) -> Result<Vec<SheetDataDensity>, Box<dyn std::error::Error>> {
-    let mut workbook: Xlsx<_> = open_workbook(xlsx_path)?;
+    config: &ClassifierConfig,
+) -> Result<Vec<SheetDataDensity>, LayoutError> {
+    let mut workbook = open_workbook_any(xlsx_path)?;
+    calculate_workbook_density(&mut workbook, config)
+}
 
-    let mut results = Vec::new();
+/// 从内存中的工作簿字节计算数据密度
+pub fn calculate_sheet_density_from_bytes(
+    data: &[u8],
+    config: &ClassifierConfig,
+) -> Result<Vec<SheetDataDensity>, LayoutError> {
+    calculate_sheet_density_from_reader(Cursor::new(data), config)
+}
 
-    // 获取所有工作表的元数据（包含可见性信息）
-    let sheet_metadata: std::collections::HashMap<String, calamine::SheetVisible> = workbook
+/// 从任意 `Read + Seek` 数据源计算数据密度，格式按内容自动识别
+pub fn calculate_sheet_density_from_reader<RS: Read + Seek>(
+    reader: RS,
+    config: &ClassifierConfig,
+) -> Result<Vec<SheetDataDensity>, LayoutError> {
+    let mut workbook = open_workbook_from_reader(reader)?;
+    calculate_workbook_density(&mut workbook, config)
+}
+
+fn calculate_workbook_density<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+    config: &ClassifierConfig,
+) -> Result<Vec<SheetDataDensity>, LayoutError> {
+    let sheets = select_worksheets(workbook, config);
+    Ok(analyze_worksheets(&sheets, config))
+}
+
+/// 按 `config.threads` 并行分析已读取的工作表，结果与工作表顺序一致
+fn analyze_worksheets(sheets: &[Worksheet], config: &ClassifierConfig) -> Vec<SheetDataDensity> {
+    parallel::parallel_map(sheets, config.threads, |sheet| {
+        analyze_sheet(
+            &sheet.name,
+            sheet.visible,
+            &sheet.range,
+            &sheet.merged_regions,
+            config,
+        )
+    })
+}
+
+/// 参与分析的工作表
+struct Worksheet {
+    name: String,
+    visible: calamine::SheetVisible,
+    range: calamine::Range<calamine::Data>,
+    merged_regions: Vec<MergedRegion>,
+}
+
+/// 读取工作簿中可见性和名称符合配置的工作表（默认只有可见的工作表）
+/// 只读取被选中的工作表，跳过的工作表不会被加载
+fn select_worksheets<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+    config: &ClassifierConfig,
+) -> Vec<Worksheet> {
+    let sheets = selected_sheets(workbook, config);
+    let mut merged_regions = merge::read_merged_regions(workbook);
+
+    sheets
+        .into_iter()
+        .filter_map(|(sheet_name, visible_status)| {
+            // 无法读取的工作表（如图表工作表）直接跳过
+            let range = workbook.worksheet_range(&sheet_name).ok()?;
+            Some(Worksheet {
+                merged_regions: merged_regions.remove(&sheet_name).unwrap_or_default(),
+                name: sheet_name,
+                visible: visible_status,
+                range,
+            })
+        })
+        .collect()
+}
+
+/// 按工作表顺序列出可见性和名称符合配置的工作表，不读取单元格数据
+/// 默认跳过隐藏和非常隐藏的工作表
+fn selected_sheets<RS: Read + Seek>(
+    workbook: &Sheets<RS>,
+    config: &ClassifierConfig,
+) -> Vec<(String, calamine::SheetVisible)> {
+    workbook
         .sheets_metadata()
         .iter()
+        .filter(|sheet| {
+            sheet.typ == calamine::SheetType::WorkSheet
+                && config.includes_visibility(sheet.visible)
+                && config.includes_sheet(&sheet.name)
+        })
         .map(|sheet| (sheet.name.clone(), sheet.visible))
+        .collect()
+}
+
+/// 计算单个工作表的数据密度等指标
+fn analyze_sheet(
+    sheet_name: &str,
+    visible_status: calamine::SheetVisible,
+    range: &calamine::Range<calamine::Data>,
+    merged_regions: &[MergedRegion],
+    config: &ClassifierConfig,
+) -> SheetDataDensity {
+    // 获取数据范围
+    let (start_row, start_col, end_row, end_col) = get_effective_range(range);
+
+    if start_row > end_row || start_col > end_col {
+        // 空工作表
+        return SheetDataDensity {
+            sheet_name: sheet_name.to_string(),
+            first_row: 0,
+            first_col: 0,
+            end_row: 0,
+            end_col: 0,
+            total_cells: 0,
+            data_cells: 0,
+            density: 0.0,
+            visible: format!("{:?}", visible_status), // 记录可见性状态
+            first_row_first_col_content: None,
+            last_row_first_col_content: None,
+            data_type_mix: 0.0,
+            column_data_types: Vec::new(),
+            error_ratio: 0.0,
+            header_row: None,
+            column_names: Vec::new(),
+            header_rows: Vec::new(),
+            header_tree: Vec::new(),
+            row_type_consistency: 0.0,
+            aspect_ratio: 0.0,
+            sampling_strategy: config.sampling.strategy,
+            sampled_rows: 0,
+            merged_cells: 0,
+            merged_cell_ratio: 0.0,
+            cell_density: 0.0,
+            shape_features: ShapeFeatures::default(),
+            crosstab: None,
+            merged_regions: merged_regions.to_vec(),
+            regions: Vec::new(),
+        };
+    }
+
+    let block = analyze_block(
+        range,
+        merged_regions,
+        (start_row, start_col, end_row, end_col),
+        config,
+    );
+
+    // 按整行/整列空白切分出独立数据块，分别计算指标
+    let regions = region::detect_regions(range, start_row, start_col, end_row, end_col)
+        .into_iter()
+        .enumerate()
+        .map(
+            |(index, (first_row, first_col, last_row, last_col))| SheetRegion {
+                region_index: index as u32,
+                ..analyze_block(
+                    range,
+                    merged_regions,
+                    (first_row, first_col, last_row, last_col),
+                    config,
+                )
+            },
+        )
         .collect();
 
-    for (sheet_name, range) in workbook.worksheets() {
-        // 检查工作表是否可见
-        let visible_status = sheet_metadata
-            .get(&sheet_name)
-            .copied()
-            .unwrap_or(calamine::SheetVisible::Visible);
+    SheetDataDensity {
+        sheet_name: sheet_name.to_string(),
+        first_row: block.first_row,
+        first_col: block.first_col,
+        end_row: block.end_row, // 保留原始end_row
+        end_col: block.end_col, // 保留原始end_col
+        total_cells: block.total_cells,
+        data_cells: block.data_cells,
+        density: block.density,
+        visible: format!("{:?}", visible_status), // 记录可见性状态
+        first_row_first_col_content: block.first_row_first_col_content,
+        last_row_first_col_content: block.last_row_first_col_content,
+        data_type_mix: block.data_type_mix,
+        column_data_types: block.column_data_types,
+        error_ratio: block.error_ratio,
+        header_row: block.header_row,
+        column_names: block.column_names,
+        header_rows: block.header_rows,
+        header_tree: block.header_tree,
+        row_type_consistency: block.row_type_consistency,
+        aspect_ratio: block.aspect_ratio,
+        sampling_strategy: block.sampling_strategy,
+        sampled_rows: block.sampled_rows,
+        merged_cells: block.merged_cells,
+        merged_cell_ratio: block.merged_cell_ratio,
+        cell_density: block.cell_density,
+        shape_features: block.shape_features,
+        crosstab: block.crosstab,
+        merged_regions: merged_regions.to_vec(),
+        regions,
+    }
+}
 
-        // 只处理可见的工作表
-        if visible_status != calamine::SheetVisible::Visible {
-            continue; // 跳过隐藏和非常隐藏的工作表
+/// 计算指定矩形范围内的密度、类型分布等指标
+/// 密度、类型分布、行一致性和宽高比只在按采样策略选出的行上计算
+/// `bounds` 为 (起始行, 起始列, 结束行, 结束列)
+fn analyze_block(
+    range: &calamine::Range<calamine::Data>,
+    merged_regions: &[MergedRegion],
+    bounds: (u32, u32, u32, u32),
+    config: &ClassifierConfig,
+) -> SheetRegion {
+    let (start_row, start_col, end_row, end_col) = bounds;
+    let rows = sampling::sample_rows(start_row, end_row, &config.sampling);
+    let locale = config.number_locale;
+    let sampled_rows = rows.len() as u32;
+
+    // 计算采样行内总单元格数和数据单元格数
+    let total_cells = sampled_rows * (end_col - start_col + 1);
+    let mut data_cells = 0;
+
+    let merged_as_filled = config.density_mode == DensityMode::MergedAsFilled;
+    let covered = merge::covered_cells(merged_regions, bounds);
+    let mut merged_cells = 0;
+    let mut filled_cells = 0;
+
+    for &row in &rows {
+        for col in start_col..=end_col {
+            // 检查是否为非空数据（非空白或全空格）
+            let filled = range
+                .get_value((row, col))
+                .is_some_and(|cell| !is_empty_cell(cell));
+            let merged = covered.contains(&(row, col));
+            merged_cells += merged as u32;
+            filled_cells += filled as u32;
+            // 按配置把合并区域覆盖的单元格计为有数据
+            if filled || (merged_as_filled && merged) {
+                data_cells += 1;
+            }
         }
+    }
 
-        // 获取数据范围
-        let (start_row, start_col, end_row, end_col) = get_effective_range(&range);
-
-        if start_row > end_row || start_col > end_col {
-            // 空工作表
-            results.push(SheetDataDensity {
-                sheet_name: sheet_name.clone(),
-                first_row: 0,
-                first_col: 0,
-                end_row: 0,
-                end_col: 0,
-                total_cells: 0,
-                data_cells: 0,
-                density: 0.0,
-                visible: format!("{:?}", visible_status), // 记录可见性状态
-                first_row_first_col_content: None,
-                last_row_first_col_content: None,
-                data_type_mix: 0.0,
-                column_data_types: Vec::new(),
-                row_type_consistency: 0.0,
-                aspect_ratio: 0.0,
-            });
-            continue;
-        }
+    // 被合并区域覆盖的单元格占比
+    let merged_cell_ratio = if total_cells > 0 {
+        merged_cells as f64 / total_cells as f64
+    } else {
+        0.0
+    };
 
-        // 限制分析前100行
-        let sample_end_row = std::cmp::min(end_row, start_row + 99); // 最多100行 (0-99)
+    let (density, cell_density) = if total_cells > 0 {
+        (
+            data_cells as f64 / total_cells as f64,
+            filled_cells as f64 / total_cells as f64,
+        )
+    } else {
+        (0.0, 0.0)
+    };
 
-        // 计算范围内总单元格数和数据单元格数
-        let total_cells = (sample_end_row - start_row + 1) * (end_col - start_col + 1);
-        let mut data_cells = 0;
+    // 获取第一行第一列的cell内容
+    let first_row_first_col_content = range
+        .get_value((start_row, start_col))
+        .map(|cell| cell.to_string());
 
-        for row in start_row..=sample_end_row {
-            for col in start_col..=end_col {
-                if let Some(cell) = range.get_value((row, col)) {
-                    // 检查是否为非空数据（非空白或全空格）
-                    if !is_empty_cell(cell) {
-                        data_cells += 1;
-                    }
-                }
-            }
-        }
+    // 获取采样最后一行第一列的cell内容
+    let last_sampled_row = rows.last().copied().unwrap_or(start_row);
+    let last_row_first_col_content = range
+        .get_value((last_sampled_row, start_col))
+        .map(|cell| cell.to_string());
 
-        let density = if total_cells > 0 {
-            data_cells as f64 / total_cells as f64
-        } else {
-            0.0
-        };
+    // 计算每列的数据类型分布
+    let mut column_data_types =
+        calculate_column_data_types(range, &rows, start_col, end_col, locale);
 
-        // 获取第一行第一列的cell内容
-        let first_row_first_col_content = range
-            .get_value((start_row, start_col))
-            .map(|cell| cell.to_string());
+    // 计算数据类型混合程度
+    let data_type_mix = calculate_data_type_mix(&column_data_types);
 
-        // 获取最后一行第一列的cell内容
-        let last_row_first_col_content = range
-            .get_value((sample_end_row, start_col))
-            .map(|cell| cell.to_string());
+    // 错误值单元格占比
+    let error_cells: u32 = column_data_types.iter().map(|c| c.error_count).sum();
+    let error_ratio = if data_cells > 0 {
+        error_cells as f64 / data_cells as f64
+    } else {
+        0.0
+    };
 
-        // 计算每列的数据类型分布
-        let column_data_types =
-            calculate_column_data_types(&range, start_row, sample_end_row, start_col, end_col);
+    // 识别表头行和列名（表头只在数据块开头若干行中寻找，不受采样策略影响）
+    let header = header::detect_header(range, merged_regions, bounds, locale);
+    let (header_row, column_names, header_rows, header_tree) = match header {
+        Some(header) => (
+            Some(header.row),
+            header.column_names,
+            header.rows,
+            header.tree,
+        ),
+        None => (None, Vec::new(), Vec::new(), Vec::new()),
+    };
 
-        // 计算数据类型混合程度
-        let data_type_mix = calculate_data_type_mix(&column_data_types);
+    // 在表头之下的采样行中识别每列的语义类型
+    let body_rows: Vec<u32> = rows
+        .iter()
+        .copied()
+        .filter(|&row| header_row.is_none_or(|header_row| row > header_row))
+        .collect();
+    for info in &mut column_data_types {
+        let semantics =
+            semantic::detect_column_semantics(range, &body_rows, info.column_index, locale);
+        info.semantic_type = semantics.semantic_type;
+        info.semantic_ratio = semantics.ratio;
+        info.semantic_matches = semantics.matches;
+    }
 
-        // 计算行间类型一致性
-        let row_type_consistency =
-            calculate_row_type_consistency(&range, start_row, sample_end_row, start_col, end_col);
+    // 区分交叉表、清单、代码对照表、日历和说明文字的特征
+    let shape_features = shape::shape_features(range, &rows, bounds, header_row, locale);
+    let crosstab = crosstab::detect_crosstab(range, merged_regions, bounds, locale);
 
-        // 计算宽高比
-        let row_count = (sample_end_row - start_row + 1) as f64;
-        let col_count = (end_col - start_col + 1) as f64;
-        let aspect_ratio = if col_count > 0.0 {
-            row_count / col_count
-        } else {
-            0.0
-        };
+    // 计算行间类型一致性
+    let row_type_consistency =
+        calculate_row_type_consistency(range, &rows, start_col, end_col, locale);
 
-        results.push(SheetDataDensity {
-            sheet_name: sheet_name.clone(),
-            first_row: start_row,
-            first_col: start_col,
-            end_row: end_row, // 保留原始end_row
-            end_col: end_col, // 保留原始end_col
-            total_cells,
-            data_cells,
-            density,
-            visible: format!("{:?}", visible_status), // 记录可见性状态
-            first_row_first_col_content,
-            last_row_first_col_content,
-            data_type_mix,
-            column_data_types,
-            row_type_consistency,
-            aspect_ratio,
-        });
+    // 计算宽高比
+    let row_count = sampled_rows as f64;
+    let col_count = (end_col - start_col + 1) as f64;
+    let aspect_ratio = if col_count > 0.0 {
+        row_count / col_count
+    } else {
+        0.0
+    };
+
+    SheetRegion {
+        region_index: 0,
+        first_row: start_row,
+        first_col: start_col,
+        end_row,
+        end_col,
+        total_cells,
+        data_cells,
+        density,
+        first_row_first_col_content,
+        last_row_first_col_content,
+        data_type_mix,
+        column_data_types,
+        error_ratio,
+        header_row,
+        column_names,
+        header_rows,
+        header_tree,
+        row_type_consistency,
+        aspect_ratio,
+        sampling_strategy: config.sampling.strategy,
+        sampled_rows,
+        merged_cells,
+        merged_cell_ratio,
+        cell_density,
+        shape_features,
+        crosstab,
+    }
+}
+
+// OLE2 复合文档文件头（.xls）
+const CFB_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+// ZIP 文件头（.xlsx/.xlsm/.xlsb/.ods）
+const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+// ZIP 中央目录结束记录签名
+const ZIP_EOCD_MAGIC: [u8; 4] = [0x50, 0x4B, 0x05, 0x06];
+// 中央目录结束记录的最小长度（不含注释）
+const ZIP_EOCD_LEN: usize = 22;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WorkbookFormat {
+    Xls,
+    Xlsx,
+    Xlsb,
+    Ods,
+}
+
+impl WorkbookFormat {
+    /// 按扩展名判断工作簿格式，与 calamine 的扩展名对应关系一致
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "xls" | "xla" => Some(WorkbookFormat::Xls),
+            "xlsx" | "xlsm" | "xlam" => Some(WorkbookFormat::Xlsx),
+            "xlsb" => Some(WorkbookFormat::Xlsb),
+            "ods" => Some(WorkbookFormat::Ods),
+            _ => None,
+        }
+    }
+}
+
+/// 根据文件内容识别工作簿格式，无法识别时返回 None
+/// OLE2 文件头 -> xls；ZIP 容器根据中央目录中的条目名区分 xlsx/xlsb/ods
+fn detect_workbook_format<R: Read + Seek>(
+    reader: &mut R,
+) -> std::io::Result<Option<WorkbookFormat>> {
+    reader.seek(SeekFrom::Start(0))?;
+    let mut magic = [0u8; 8];
+    let n = reader.read(&mut magic)?;
+
+    let format = if n == CFB_MAGIC.len() && magic == CFB_MAGIC {
+        Some(WorkbookFormat::Xls)
+    } else if n >= ZIP_MAGIC.len() && magic[..ZIP_MAGIC.len()] == ZIP_MAGIC {
+        detect_zip_workbook_format(reader)?
+    } else {
+        None
+    };
+
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(format)
+}
+
+/// 读取 ZIP 中央目录，根据其中的条目名判断工作簿格式
+fn detect_zip_workbook_format<R: Read + Seek>(
+    reader: &mut R,
+) -> std::io::Result<Option<WorkbookFormat>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let tail_len = std::cmp::min(len, (ZIP_EOCD_LEN + u16::MAX as usize) as u64);
+    reader.seek(SeekFrom::Start(len - tail_len))?;
+    let mut tail = vec![0u8; tail_len as usize];
+    reader.read_exact(&mut tail)?;
+
+    let Some(eocd) = tail
+        .windows(ZIP_EOCD_MAGIC.len())
+        .rposition(|w| w == ZIP_EOCD_MAGIC)
+    else {
+        return Ok(None);
+    };
+    if eocd + ZIP_EOCD_LEN > tail.len() {
+        return Ok(None);
+    }
+
+    let read_u32 = |pos: usize| u32::from_le_bytes(tail[pos..pos + 4].try_into().unwrap());
+    let cd_size = read_u32(eocd + 12) as u64;
+    let cd_offset = read_u32(eocd + 16) as u64;
+    if cd_offset.saturating_add(cd_size) > len {
+        return Ok(None);
     }
 
-    Ok(results)
+    reader.seek(SeekFrom::Start(cd_offset))?;
+    let mut directory = vec![0u8; cd_size as usize];
+    reader.read_exact(&mut directory)?;
+
+    let contains = |name: &[u8]| directory.windows(name.len()).any(|w| w == name);
+    let format = if contains(b"xl/workbook.bin") {
+        Some(WorkbookFormat::Xlsb)
+    } else if contains(b"xl/workbook.xml") {
+        Some(WorkbookFormat::Xlsx)
+    } else if contains(b"content.xml") {
+        Some(WorkbookFormat::Ods)
+    } else {
+        None
+    };
+
+    Ok(format)
+}
+
+/// 按已识别的格式从 reader 打开工作簿
+fn open_workbook_with_format<RS: Read + Seek>(
+    reader: RS,
+    format: WorkbookFormat,
+) -> Result<Sheets<RS>, calamine::Error> {
+    Ok(match format {
+        WorkbookFormat::Xls => Sheets::Xls(open_workbook_from_rs::<Xls<_>, _>(reader)?),
+        WorkbookFormat::Xlsx => Sheets::Xlsx(open_workbook_from_rs::<Xlsx<_>, _>(reader)?),
+        WorkbookFormat::Xlsb => Sheets::Xlsb(open_workbook_from_rs::<Xlsb<_>, _>(reader)?),
+        WorkbookFormat::Ods => Sheets::Ods(open_workbook_from_rs::<Ods<_>, _>(reader)?),
+    })
+}
+
+/// 打开任意格式的工作簿
+/// 优先按文件内容识别格式（例如导出为 .xls 的 xlsx 文件），无法识别时按扩展名打开以给出具体的解析错误，
+/// 扩展名也不是工作簿时返回 `UnsupportedFormat`
+#[cfg(not(target_arch = "wasm32"))]
+fn open_workbook_any<P: AsRef<Path>>(path: P) -> Result<Sheets<BufReader<File>>, LayoutError> {
+    let path = path.as_ref();
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let format = match detect_workbook_format(&mut reader)? {
+        Some(format) => format,
+        None => WorkbookFormat::from_extension(path).ok_or(LayoutError::UnsupportedFormat)?,
+    };
+    Ok(open_workbook_with_format(reader, format)?)
+}
+
+/// 从任意 `Read + Seek` 数据源打开工作簿，格式仅按内容识别
+fn open_workbook_from_reader<RS: Read + Seek>(mut reader: RS) -> Result<Sheets<RS>, LayoutError> {
+    match detect_workbook_format(&mut reader)? {
+        Some(format) => Ok(open_workbook_with_format(reader, format)?),
+        None => Err(LayoutError::UnsupportedFormat),
+    }
 }
 
 fn get_effective_range(range: &calamine::Range<calamine::Data>) -> (u32, u32, u32, u32) {
@@ -200,104 +833,117 @@ fn get_effective_range(range: &calamine::Range<calamine::Data>) -> (u32, u32, u3
     (start_row, start_col, end_row, end_col)
 }
 
+/// 空白单元格：空值或只含空白字符的字符串；错误值（#N/A 等）算作有数据
 fn is_empty_cell(cell: &calamine::Data) -> bool {
     match cell {
-        calamine::Data::Empty | calamine::Data::Error(_) => true,
+        calamine::Data::Empty => true,
         calamine::Data::String(s) => s.trim().is_empty(),
         _ => false,
     }
 }
 
-// 静态正则表达式，用于匹配数值型数据
-lazy_static! {
-    static ref PERCENTAGE_RE: Regex = Regex::new(r#"^-?\d*\.?\d+%$"#).unwrap();
-    static ref THOUSANDS_RE: Regex = Regex::new(r#"^-?\d{1,3}(,\d{3})*(\.\d+)?$"#).unwrap();
-    static ref NUMBER_RE: Regex = Regex::new(r#"^-?\d+\.?\d*$"#).unwrap();
-}
-
-/// 检查字符串是否为数值型数据
-/// 支持：整数、小数、含千分位数、百分数
-fn is_numeric_string(s: &str) -> bool {
-    let s_trimmed = s.trim();
-    if s_trimmed.is_empty() {
-        return false;
-    }
-
-    // 检查是否为百分数 (例如: 50%, -25.5%)
-    if PERCENTAGE_RE.is_match(s_trimmed) {
-        return true;
-    }
-
-    // 检查是否为含千分位的数字 (例如: 1,234.56, -1,234.56)
-    if THOUSANDS_RE.is_match(s_trimmed) {
-        return true;
-    }
-
-    // 检查是否为普通小数或整数 (例如: 123.45, -67.89, 123, -45)
-    if NUMBER_RE.is_match(s_trimmed) {
-        return true;
+/// 把单元格转换为便于阅读的文本，日期时间按 ISO 8601 格式输出
+fn format_cell(cell: &calamine::Data) -> String {
+    match cell {
+        calamine::Data::DateTime(dt) if dt.is_duration() => {
+            let total_seconds = (dt.as_f64() * 86400.0).round() as i64;
+            format!(
+                "{}:{:02}:{:02}",
+                total_seconds / 3600,
+                (total_seconds / 60) % 60,
+                total_seconds % 60
+            )
+        }
+        calamine::Data::DateTime(dt) => {
+            let (year, month, day, hour, minute, second, _) = dt.to_ymd_hms_milli();
+            if dt.as_f64() < 1.0 {
+                format!("{:02}:{:02}:{:02}", hour, minute, second)
+            } else if hour == 0 && minute == 0 && second == 0 {
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            } else {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                    year, month, day, hour, minute, second
+                )
+            }
+        }
+        _ => cell.to_string().trim().to_string(),
     }
-
-    false
 }
 
-/// 检查单元格是否包含数值型数据
-fn is_numeric_cell(cell: &calamine::Data) -> bool {
-    match cell {
-        calamine::Data::Int(_) | calamine::Data::Float(_) => true,
-        calamine::Data::String(s) => is_numeric_string(s),
-        _ => false,
+/// 把 0 起始的行列号转换为 A1 形式的单元格地址
+fn cell_reference(row: u32, col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.push((b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
     }
+    letters.iter().rev().collect::<String>() + &(row + 1).to_string()
 }
 
-/// 计算每列的数据类型分布
+/// 计算每列在采样行中的数据类型分布
 fn calculate_column_data_types(
     range: &calamine::Range<calamine::Data>,
-    start_row: u32,
-    end_row: u32,
+    rows: &[u32],
     start_col: u32,
     end_col: u32,
+    locale: NumberLocale,
 ) -> Vec<ColumnDataTypeInfo> {
     let mut column_info = Vec::new();
 
     for col in start_col..=end_col {
-        let mut numeric_count = 0;
-        let mut text_count = 0;
-        let mut total_count = 0;
+        let mut info = ColumnDataTypeInfo {
+            column_index: col,
+            ..ColumnDataTypeInfo::default()
+        };
 
-        for row in start_row..=end_row {
-            if let Some(cell) = range.get_value((row, col)) {
-                if !is_empty_cell(cell) {
-                    total_count += 1;
-                    if is_numeric_cell(cell) {
-                        numeric_count += 1;
-                    } else {
-                        // 将非数值型数据视为文本型数据
-                        text_count += 1;
+        for &row in rows {
+            let Some(cell) = range.get_value((row, col)) else {
+                continue;
+            };
+            let Some(category) = datatype::cell_category(cell, locale) else {
+                continue;
+            };
+            info.total_count += 1;
+            match category {
+                CellCategory::Numeric => info.numeric_count += 1,
+                CellCategory::Text => info.text_count += 1,
+                CellCategory::Date => info.date_count += 1,
+                CellCategory::DateTime => info.datetime_count += 1,
+                CellCategory::Time => info.time_count += 1,
+                CellCategory::Duration => info.duration_count += 1,
+                CellCategory::Bool => info.bool_count += 1,
+                CellCategory::Error => {
+                    info.error_count += 1;
+                    if let Some(kind) = datatype::error_kind(cell) {
+                        *info.error_kinds.entry(kind).or_default() += 1;
                     }
                 }
             }
         }
 
-        let numeric_type_ratio = if total_count > 0 {
-            numeric_count as f64 / total_count as f64
-        } else {
-            0.0
-        };
+        if info.total_count > 0 {
+            let ratio = |count: u32| count as f64 / info.total_count as f64;
+            info.numeric_type_ratio = ratio(info.numeric_count);
+            info.date_type_ratio = ratio(info.date_count);
+            info.datetime_type_ratio = ratio(info.datetime_count);
+            info.time_type_ratio = ratio(info.time_count);
+            info.duration_type_ratio = ratio(info.duration_count);
+            info.bool_type_ratio = ratio(info.bool_count);
+            info.error_type_ratio = ratio(info.error_count);
+        }
 
-        column_info.push(ColumnDataTypeInfo {
-            column_index: col,
-            numeric_count,
-            text_count,
-            total_count,
-            numeric_type_ratio,
-        });
+        column_info.push(info);
     }
 
     column_info
 }
 
 /// 使用多样性指数（香农熵）计算数据类型混合程度
+/// 熵在数值、文本、日期、日期时间、时间、时长、布尔、错误八个类别上计算，
+/// 除以 ln(列中出现的类别数，至少为2) 标准化到 0-1，只有数值和文本两类时与二分类结果相同
 fn calculate_data_type_mix(column_data_types: &[ColumnDataTypeInfo]) -> f64 {
     if column_data_types.is_empty() {
         return 0.0;
@@ -309,20 +955,18 @@ fn calculate_data_type_mix(column_data_types: &[ColumnDataTypeInfo]) -> f64 {
     for col_info in column_data_types {
         if col_info.total_count > 0 {
             // 计算香农熵
-            let p_numeric = col_info.numeric_count as f64 / col_info.total_count as f64;
-            let p_text = col_info.text_count as f64 / col_info.total_count as f64;
-
+            let counts = col_info.category_counts();
             let mut entropy = 0.0;
-            if p_numeric > 0.0 {
-                entropy -= p_numeric * p_numeric.ln();
-            }
-            if p_text > 0.0 {
-                entropy -= p_text * p_text.ln();
+            for &count in &counts {
+                if count > 0 {
+                    let p = count as f64 / col_info.total_count as f64;
+                    entropy -= p * p.ln();
+                }
             }
 
-            // 标准化熵值 (除以 ln(2) 以确保最大值为 1)
+            let present = counts.iter().filter(|&&count| count > 0).count().max(2);
             let normalized_entropy = if entropy > 0.0 {
-                entropy / 2.0f64.ln()
+                entropy / (present as f64).ln()
             } else {
                 0.0
             };
@@ -344,18 +988,14 @@ fn calculate_data_type_mix(column_data_types: &[ColumnDataTypeInfo]) -> f64 {
 /// 标准差越小，表示行间类型模式越一致（数据表特征）
 fn calculate_row_type_consistency(
     range: &calamine::Range<calamine::Data>,
-    start_row: u32,
-    end_row: u32,
+    rows: &[u32],
     start_col: u32,
     end_col: u32,
+    locale: NumberLocale,
 ) -> f64 {
-    if start_row > end_row {
-        return 0.0;
-    }
-
     let mut row_numeric_ratios = Vec::new();
 
-    for row in start_row..=end_row {
+    for &row in rows {
         let mut numeric_count = 0;
         let mut total_count = 0;
 
@@ -363,7 +1003,7 @@ fn calculate_row_type_consistency(
             if let Some(cell) = range.get_value((row, col)) {
                 if !is_empty_cell(cell) {
                     total_count += 1;
-                    if is_numeric_cell(cell) {
+                    if numeric::is_numeric_cell(cell, locale) {
                         numeric_count += 1;
                     }
                 }
@@ -392,77 +1032,58 @@ fn calculate_row_type_consistency(
     // 将标准差转换为一致性分数（0-1）
     // 标准差越小，一致性越高
     // 使用 sigmoid 函数映射：consistency = 1 / (1 + std_dev * 2)
-    let consistency = 1.0 / (1.0 + std_dev * 3.0);
-    consistency
+    1.0 / (1.0 + std_dev * 3.0)
 }
 
-/// 根据密度、数据类型混合度、行间类型一致性和宽高比对工作表进行分类
-pub fn classify_sheet(sheet_data: &SheetDataDensity) -> ClassifiedSheet {
-    // 忽略density=0的sheet
-    if sheet_data.density == 0.0 {
-        return ClassifiedSheet {
-            sheet_name: sheet_data.sheet_name.clone(),
-            first_row: sheet_data.first_row,
-            first_col: sheet_data.first_col,
-            end_row: sheet_data.end_row,
-            end_col: sheet_data.end_col,
-            total_cells: sheet_data.total_cells,
-            data_cells: sheet_data.data_cells,
+impl LayoutFeatures {
+    fn from_sheet(sheet_data: &SheetDataDensity) -> Self {
+        LayoutFeatures {
             density: sheet_data.density,
-            visible: sheet_data.visible.clone(),
-            first_row_first_col_content: sheet_data.first_row_first_col_content.clone(),
-            last_row_first_col_content: sheet_data.last_row_first_col_content.clone(),
             data_type_mix: sheet_data.data_type_mix,
-            column_data_types: sheet_data.column_data_types.clone(),
             row_type_consistency: sheet_data.row_type_consistency,
             aspect_ratio: sheet_data.aspect_ratio,
-            sheet_type: SheetType::Unknown,
-            classification_reason: "Density is zero".to_string(),
-        };
+            col_count: sheet_data.end_col - sheet_data.first_col + 1,
+            merged_cell_ratio: sheet_data.merged_cell_ratio,
+            cell_density: sheet_data.cell_density,
+            row_count: sheet_data.sampled_rows,
+            shape: sheet_data.shape_features.clone(),
+            crosstab_detected: sheet_data.crosstab.is_some(),
+        }
     }
 
-    // 综合分类逻辑
-    // 1. 极高密度 -> 数据表
-    // 2. 高密度 + 高行一致性 + 列数较多 -> 数据表
-    // 3. 宽高比 > 5 + 列数 <= 4 -> 表单（垂直排列的键值对表单）
-    // 4. 宽表特征（多列+扁平）-> 数据表
-    // 5. 低密度 + 高混合度 + 列数较多 -> 数据表
-    // 6. 其他情况根据混合度判断
-    let col_count = sheet_data.end_col - sheet_data.first_col + 1;
-
-    let sheet_type = if sheet_data.density > 0.70 {
-        // 极高密度几乎肯定是数据表
-        SheetType::Data
-    } else if sheet_data.aspect_ratio > 4.0 && col_count <= 4 && sheet_data.density > 0.35 {
-        // 高瘦结构 + 少列 + 中等密度 -> 表单（垂直键值对表单）
-        SheetType::Form
-    } else if sheet_data.density > 0.46 && col_count > 4 && sheet_data.row_type_consistency > 0.50 {
-        // 高密度、多列、行一致性中等以上 -> 数据表
-        SheetType::Data
-    } else if sheet_data.density > 0.40 && sheet_data.row_type_consistency > 0.80 {
-        // 行结构高度一致且密度中等以上 -> 数据表（需要更高的一致性阈值）
-        SheetType::Data
-    } else if col_count > 10 && sheet_data.aspect_ratio < 0.5 && sheet_data.density > 0.35 {
-        // 宽表特征：列数多(>10) + 宽高比低(<0.5) + 密度中等以上 -> 数据表
-        SheetType::Data
-    } else if sheet_data.density > 0.46 && sheet_data.data_type_mix > 0.35 {
-        // 原逻辑：高密度且数据类型混合度高
-        SheetType::Data
-    } else if sheet_data.density > 0.35 && sheet_data.data_type_mix > 0.35 && col_count > 5 {
-        // 密度中等 + 数据类型混合度高 + 列数较多 -> 数据表
-        SheetType::Data
-    } else {
-        // 其他情况视为表单
-        SheetType::Form
-    };
+    fn from_region(region: &SheetRegion) -> Self {
+        LayoutFeatures {
+            density: region.density,
+            data_type_mix: region.data_type_mix,
+            row_type_consistency: region.row_type_consistency,
+            aspect_ratio: region.aspect_ratio,
+            col_count: region.end_col - region.first_col + 1,
+            merged_cell_ratio: region.merged_cell_ratio,
+            cell_density: region.cell_density,
+            row_count: region.sampled_rows,
+            shape: region.shape_features.clone(),
+            crosstab_detected: region.crosstab.is_some(),
+        }
+    }
+}
 
-    let reason = format!(
-        "density: {:.3}, data_type_mix: {:.3}, row_consistency: {:.3}, aspect_ratio: {:.1}",
-        sheet_data.density,
-        sheet_data.data_type_mix,
-        sheet_data.row_type_consistency,
-        sheet_data.aspect_ratio
-    );
+/// 根据密度、数据类型混合度、行间类型一致性和宽高比对工作表进行分类
+pub fn classify_sheet(sheet_data: &SheetDataDensity) -> ClassifiedSheet {
+    classify_sheet_with_config(sheet_data, &ClassifierConfig::default())
+}
+
+/// 使用指定的阈值配置对工作表进行分类
+pub fn classify_sheet_with_config(
+    sheet_data: &SheetDataDensity,
+    config: &ClassifierConfig,
+) -> ClassifiedSheet {
+    let LayoutClassification {
+        sheet_type,
+        layout_type,
+        reason,
+        confidence,
+        explanation,
+    } = classify_layout(&LayoutFeatures::from_sheet(sheet_data), config);
 
     ClassifiedSheet {
         sheet_name: sheet_data.sheet_name.clone(),
@@ -478,64 +1099,347 @@ pub fn classify_sheet(sheet_data: &SheetDataDensity) -> ClassifiedSheet {
         last_row_first_col_content: sheet_data.last_row_first_col_content.clone(),
         data_type_mix: sheet_data.data_type_mix,
         column_data_types: sheet_data.column_data_types.clone(),
+        error_ratio: sheet_data.error_ratio,
+        header_row: sheet_data
+            .header_row
+            .filter(|_| sheet_type == SheetType::Data),
+        column_names: if sheet_type == SheetType::Data {
+            sheet_data.column_names.clone()
+        } else {
+            Vec::new()
+        },
+        header_rows: if sheet_type == SheetType::Data {
+            sheet_data.header_rows.clone()
+        } else {
+            Vec::new()
+        },
+        header_tree: if sheet_type == SheetType::Data {
+            sheet_data.header_tree.clone()
+        } else {
+            Vec::new()
+        },
         row_type_consistency: sheet_data.row_type_consistency,
         aspect_ratio: sheet_data.aspect_ratio,
+        sampling_strategy: sheet_data.sampling_strategy,
+        sampled_rows: sheet_data.sampled_rows,
+        merged_cells: sheet_data.merged_cells,
+        merged_cell_ratio: sheet_data.merged_cell_ratio,
+        cell_density: sheet_data.cell_density,
+        shape_features: sheet_data.shape_features.clone(),
+        crosstab: sheet_data
+            .crosstab
+            .clone()
+            .filter(|_| layout_type == Some(LayoutType::Crosstab)),
+        merged_regions: sheet_data.merged_regions.clone(),
+        sheet_type,
+        layout_type,
+        classification_reason: reason,
+        confidence,
+        explanation,
+        regions: sheet_data
+            .regions
+            .iter()
+            .map(|region| classify_region_with_config(region, config))
+            .collect(),
+    }
+}
+
+/// 对单个数据块进行分类，规则与工作表分类相同
+pub fn classify_region(region: &SheetRegion) -> ClassifiedRegion {
+    classify_region_with_config(region, &ClassifierConfig::default())
+}
+
+/// 使用指定的阈值配置对单个数据块进行分类
+pub fn classify_region_with_config(
+    region: &SheetRegion,
+    config: &ClassifierConfig,
+) -> ClassifiedRegion {
+    // 单个单元格（如标题、备注）无法判断布局
+    let LayoutClassification {
+        sheet_type,
+        layout_type,
+        reason,
+        confidence,
+        explanation,
+    } = if region.data_cells <= 1 {
+        LayoutClassification::fixed(SheetType::Unknown, "single_cell", "Single cell region")
+    } else {
+        classify_layout(&LayoutFeatures::from_region(region), config)
+    };
+
+    ClassifiedRegion {
+        region_index: region.region_index,
+        first_row: region.first_row,
+        first_col: region.first_col,
+        end_row: region.end_row,
+        end_col: region.end_col,
+        total_cells: region.total_cells,
+        data_cells: region.data_cells,
+        density: region.density,
+        first_row_first_col_content: region.first_row_first_col_content.clone(),
+        last_row_first_col_content: region.last_row_first_col_content.clone(),
+        data_type_mix: region.data_type_mix,
+        column_data_types: region.column_data_types.clone(),
+        error_ratio: region.error_ratio,
+        header_row: region.header_row.filter(|_| sheet_type == SheetType::Data),
+        column_names: if sheet_type == SheetType::Data {
+            region.column_names.clone()
+        } else {
+            Vec::new()
+        },
+        header_rows: if sheet_type == SheetType::Data {
+            region.header_rows.clone()
+        } else {
+            Vec::new()
+        },
+        header_tree: if sheet_type == SheetType::Data {
+            region.header_tree.clone()
+        } else {
+            Vec::new()
+        },
+        row_type_consistency: region.row_type_consistency,
+        aspect_ratio: region.aspect_ratio,
+        sampling_strategy: region.sampling_strategy,
+        sampled_rows: region.sampled_rows,
+        merged_cells: region.merged_cells,
+        merged_cell_ratio: region.merged_cell_ratio,
+        cell_density: region.cell_density,
+        shape_features: region.shape_features.clone(),
+        crosstab: region
+            .crosstab
+            .clone()
+            .filter(|_| layout_type == Some(LayoutType::Crosstab)),
         sheet_type,
+        layout_type,
         classification_reason: reason,
+        confidence,
+        explanation,
     }
 }
 
 /// 对整个Excel文件的所有工作表进行分类（忽略density=0的sheet）
-pub fn classify_excel_sheets(
+#[cfg(not(target_arch = "wasm32"))]
+pub fn classify_excel_sheets(xlsx_path: &str) -> Result<Vec<ClassifiedSheet>, LayoutError> {
+    classify_excel_sheets_with_config(xlsx_path, &ClassifierConfig::default())
+}
+
+/// 使用指定的阈值配置对整个Excel文件进行分类
+#[cfg(not(target_arch = "wasm32"))]
+pub fn classify_excel_sheets_with_config(
     xlsx_path: &str,
-) -> Result<Vec<ClassifiedSheet>, Box<dyn std::error::Error>> {
-    let sheets = calculate_sheet_density(xlsx_path)?;
+    config: &ClassifierConfig,
+) -> Result<Vec<ClassifiedSheet>, LayoutError> {
+    let sheets = calculate_sheet_density_with_config(xlsx_path, config)?;
+    Ok(classify_sheets(sheets, config))
+}
 
+/// 使用指定的阈值配置对内存中的工作簿字节进行分类
+pub fn classify_excel_sheets_from_bytes(
+    data: &[u8],
+    config: &ClassifierConfig,
+) -> Result<Vec<ClassifiedSheet>, LayoutError> {
+    classify_excel_sheets_from_reader(Cursor::new(data), config)
+}
+
+/// 使用指定的阈值配置对任意 `Read + Seek` 数据源中的工作簿进行分类
+pub fn classify_excel_sheets_from_reader<RS: Read + Seek>(
+    reader: RS,
+    config: &ClassifierConfig,
+) -> Result<Vec<ClassifiedSheet>, LayoutError> {
+    let sheets = calculate_sheet_density_from_reader(reader, config)?;
+    Ok(classify_sheets(sheets, config))
+}
+
+fn classify_sheets(
+    sheets: Vec<SheetDataDensity>,
+    config: &ClassifierConfig,
+) -> Vec<ClassifiedSheet> {
     // 对每个sheet进行分类，忽略density=0的sheet
-    let classified_sheets: Vec<ClassifiedSheet> = sheets
+    sheets
         .into_iter()
-        .map(|sheet| classify_sheet(&sheet))
+        .map(|sheet| classify_sheet_with_config(&sheet, config))
         .filter(|classified| classified.density != 0.0) // 过滤掉密度为0的sheet
-        .collect();
+        .collect()
+}
 
-    Ok(classified_sheets)
+/// 提取工作簿中所有 Form 类型工作表的键值对字段
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract_form_fields(xlsx_path: &str) -> Result<Vec<SheetFormFields>, LayoutError> {
+    extract_form_fields_with_config(xlsx_path, &ClassifierConfig::default())
 }
 
-// C FFI functions for use as a dynamic library from Python
+/// 使用指定的阈值配置判断 Form 类型工作表并提取键值对字段
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract_form_fields_with_config(
+    xlsx_path: &str,
+    config: &ClassifierConfig,
+) -> Result<Vec<SheetFormFields>, LayoutError> {
+    let mut workbook = open_workbook_any(xlsx_path)?;
+    Ok(extract_workbook_form_fields(&mut workbook, config))
+}
 
-/// C function to classify Excel sheets and return results as JSON string
-/// The caller is responsible for freeing the returned string using free_c_string
-#[no_mangle]
-pub unsafe extern "C" fn classify_excel_sheets_c(xlsx_path: *const c_char) -> *mut c_char {
-    if xlsx_path.is_null() {
-        return std::ptr::null_mut();
-    }
+/// 从内存中的工作簿字节、使用指定的阈值配置提取表单字段
+pub fn extract_form_fields_from_bytes(
+    data: &[u8],
+    config: &ClassifierConfig,
+) -> Result<Vec<SheetFormFields>, LayoutError> {
+    extract_form_fields_from_reader(Cursor::new(data), config)
+}
+
+/// 从任意 `Read + Seek` 数据源、使用指定的阈值配置提取表单字段
+pub fn extract_form_fields_from_reader<RS: Read + Seek>(
+    reader: RS,
+    config: &ClassifierConfig,
+) -> Result<Vec<SheetFormFields>, LayoutError> {
+    let mut workbook = open_workbook_from_reader(reader)?;
+    Ok(extract_workbook_form_fields(&mut workbook, config))
+}
+
+fn extract_workbook_form_fields<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+    config: &ClassifierConfig,
+) -> Vec<SheetFormFields> {
+    let sheets = select_worksheets(workbook, config);
+    let analyzed = analyze_worksheets(&sheets, config);
+    sheets
+        .iter()
+        .zip(analyzed)
+        .filter(|(_, sheet_data)| {
+            classify_sheet_with_config(sheet_data, config).sheet_type == SheetType::Form
+        })
+        .map(|(sheet, _)| SheetFormFields {
+            sheet_name: sheet.name.clone(),
+            fields: extract_form_fields_from_range(&sheet.range, config.number_locale),
+        })
+        .collect()
+}
+
+/// 逐行提取工作簿中所有 Data 类型工作表的记录
+/// 工作表逐个加载，每条记录生成后立即交给 `on_record`（参数为工作表名称和记录）
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract_records<F: FnMut(&str, Record)>(
+    xlsx_path: &str,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    extract_records_with_config(xlsx_path, &ClassifierConfig::default(), on_record)
+}
+
+/// 使用指定的阈值配置判断 Data 类型工作表并逐行提取记录
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract_records_with_config<F: FnMut(&str, Record)>(
+    xlsx_path: &str,
+    config: &ClassifierConfig,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    let mut workbook = open_workbook_any(xlsx_path)?;
+    extract_workbook_records(&mut workbook, config, on_record)
+}
 
-    let path_c_str = unsafe {
-        match CStr::from_ptr(xlsx_path).to_str() {
-            Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
+/// 从内存中的工作簿字节、使用指定的阈值配置逐行提取记录
+pub fn extract_records_from_bytes<F: FnMut(&str, Record)>(
+    data: &[u8],
+    config: &ClassifierConfig,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    extract_records_from_reader(Cursor::new(data), config, on_record)
+}
+
+/// 从任意 `Read + Seek` 数据源、使用指定的阈值配置逐行提取记录
+pub fn extract_records_from_reader<RS: Read + Seek, F: FnMut(&str, Record)>(
+    reader: RS,
+    config: &ClassifierConfig,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    let mut workbook = open_workbook_from_reader(reader)?;
+    extract_workbook_records(&mut workbook, config, on_record)
+}
+
+fn extract_workbook_records<RS: Read + Seek, F: FnMut(&str, Record)>(
+    workbook: &mut Sheets<RS>,
+    config: &ClassifierConfig,
+    mut on_record: F,
+) -> Result<(), LayoutError> {
+    let sheets = selected_sheets(workbook, config);
+    let merged_regions = merge::read_merged_regions(workbook);
+
+    for (sheet_name, visible_status) in sheets {
+        let range = workbook.worksheet_range(&sheet_name)?;
+        let merged = merged_regions
+            .get(&sheet_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let sheet_data = analyze_sheet(&sheet_name, visible_status, &range, merged, config);
+        let classified = classify_sheet_with_config(&sheet_data, config);
+        if classified.sheet_type != SheetType::Data {
+            continue;
         }
-    };
 
-    match classify_excel_sheets(path_c_str) {
-        Ok(results) => match serde_json::to_string(&results) {
-            Ok(json_string) => match CString::new(json_string) {
-                Ok(c_string) => c_string.into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            },
-            Err(_) => std::ptr::null_mut(),
-        },
-        Err(_) => std::ptr::null_mut(),
+        for record in records_from_range(&range, &classified) {
+            on_record(&sheet_name, record);
+        }
     }
+
+    Ok(())
+}
+
+/// 把工作簿中所有交叉表（`layout_type` 为 Crosstab）的工作表逆透视为长格式记录
+/// 每条记录包含行标签、列标签和数值，生成后立即交给 `on_record`（参数为工作表名称和记录）
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unpivot_crosstabs<F: FnMut(&str, Record)>(
+    xlsx_path: &str,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    unpivot_crosstabs_with_config(xlsx_path, &ClassifierConfig::default(), on_record)
+}
+
+/// 使用指定的阈值配置判断交叉表并逆透视为长格式记录
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unpivot_crosstabs_with_config<F: FnMut(&str, Record)>(
+    xlsx_path: &str,
+    config: &ClassifierConfig,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    let mut workbook = open_workbook_any(xlsx_path)?;
+    unpivot_workbook_crosstabs(&mut workbook, config, on_record)
+}
+
+/// 从内存中的工作簿字节、使用指定的阈值配置逆透视交叉表
+pub fn unpivot_crosstabs_from_bytes<F: FnMut(&str, Record)>(
+    data: &[u8],
+    config: &ClassifierConfig,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    unpivot_crosstabs_from_reader(Cursor::new(data), config, on_record)
 }
 
-/// C function to free strings allocated by Rust
-#[no_mangle]
-pub unsafe extern "C" fn free_c_string(ptr: *mut c_char) {
-    if !ptr.is_null() {
-        let _ = unsafe { CString::from_raw(ptr) };
+/// 从任意 `Read + Seek` 数据源、使用指定的阈值配置逆透视交叉表
+pub fn unpivot_crosstabs_from_reader<RS: Read + Seek, F: FnMut(&str, Record)>(
+    reader: RS,
+    config: &ClassifierConfig,
+    on_record: F,
+) -> Result<(), LayoutError> {
+    let mut workbook = open_workbook_from_reader(reader)?;
+    unpivot_workbook_crosstabs(&mut workbook, config, on_record)
+}
+
+fn unpivot_workbook_crosstabs<RS: Read + Seek, F: FnMut(&str, Record)>(
+    workbook: &mut Sheets<RS>,
+    config: &ClassifierConfig,
+    mut on_record: F,
+) -> Result<(), LayoutError> {
+    let sheets = select_worksheets(workbook, config);
+    for (sheet, sheet_data) in sheets.iter().zip(analyze_worksheets(&sheets, config)) {
+        let classified = classify_sheet_with_config(&sheet_data, config);
+        let Some(layout) = classified.crosstab else {
+            continue;
+        };
+
+        for record in unpivot_range(&sheet.range, &layout) {
+            on_record(&sheet.name, record);
+        }
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -575,9 +1479,24 @@ mod tests {
                 text_count: 5,
                 total_count: 10,
                 numeric_type_ratio: 0.5,
+                ..ColumnDataTypeInfo::default()
             }],
+            error_ratio: 0.0,
+            header_row: None,
+            column_names: Vec::new(),
+            header_rows: Vec::new(),
+            header_tree: Vec::new(),
             row_type_consistency: 0.7,
             aspect_ratio: 1.0,
+            sampling_strategy: SamplingStrategy::Head,
+            sampled_rows: 11,
+            merged_cells: 0,
+            merged_cell_ratio: 0.0,
+            cell_density: 0.0,
+            shape_features: ShapeFeatures::default(),
+            crosstab: None,
+            merged_regions: Vec::new(),
+            regions: Vec::new(),
         };
 
         assert_eq!(sheet_data.sheet_name, "Test Sheet");
@@ -598,6 +1517,7 @@ mod tests {
     #[test]
     fn test_is_numeric_string() {
         // 测试正则表达式对数值型数据的识别
+        let is_numeric_string = |s| numeric::is_numeric_string(s, NumberLocale::default());
         assert!(is_numeric_string("123"));
         assert!(is_numeric_string("-45"));
         assert!(is_numeric_string("3.14"));
@@ -625,6 +1545,7 @@ mod tests {
                 text_count: 3,
                 total_count: 6,
                 numeric_type_ratio: 0.5,
+                ..ColumnDataTypeInfo::default()
             },
             ColumnDataTypeInfo {
                 column_index: 1,
@@ -632,6 +1553,7 @@ mod tests {
                 text_count: 2,
                 total_count: 4,
                 numeric_type_ratio: 0.5,
+                ..ColumnDataTypeInfo::default()
             },
         ];
 
@@ -648,6 +1570,7 @@ mod tests {
                 text_count: 0,
                 total_count: 5,
                 numeric_type_ratio: 1.0,
+                ..ColumnDataTypeInfo::default()
             },
             ColumnDataTypeInfo {
                 column_index: 1,
@@ -655,6 +1578,7 @@ mod tests {
                 text_count: 0,
                 total_count: 5,
                 numeric_type_ratio: 1.0,
+                ..ColumnDataTypeInfo::default()
             },
         ];
 
@@ -662,4 +1586,432 @@ mod tests {
         let mix = calculate_data_type_mix(&column_data_types);
         assert!(mix < 0.1, "混合程度应该较低，当前值为: {}", mix);
     }
+
+    #[test]
+    fn test_calculate_column_data_types_temporal() {
+        let mut range = calamine::Range::new((0, 0), (3, 1));
+        range.set_value((0, 0), calamine::Data::String("2024-01-05".to_string()));
+        range.set_value((1, 0), calamine::Data::String("2024年1月6日".to_string()));
+        range.set_value(
+            (2, 0),
+            calamine::Data::String("2024-01-07 08:30".to_string()),
+        );
+        range.set_value((3, 0), calamine::Data::Int(3));
+        for row in 0..4 {
+            range.set_value((row, 1), calamine::Data::String("08:30".to_string()));
+        }
+
+        let columns = calculate_column_data_types(&range, &[0, 1, 2, 3], 0, 1, NumberLocale::Auto);
+        assert_eq!(columns[0].date_count, 2);
+        assert_eq!(columns[0].datetime_count, 1);
+        assert_eq!(columns[0].numeric_count, 1);
+        assert_eq!(columns[0].text_count, 0);
+        assert_eq!(columns[0].date_type_ratio, 0.5);
+        assert_eq!(columns[1].time_count, 4);
+        assert_eq!(columns[1].time_type_ratio, 1.0);
+
+        // 三类各占一部分的列混合度在 0-1 之间，单一时间列为 0
+        let mix = calculate_data_type_mix(&columns[..1]);
+        assert!(mix > 0.9 && mix <= 1.0, "当前值为: {}", mix);
+        assert_eq!(calculate_data_type_mix(&columns[1..]), 0.0);
+    }
+
+    #[test]
+    fn test_bool_and_error_cells() {
+        // 全是 #N/A 和 TRUE/FALSE 的表不应显得稀疏或全是文本
+        let mut range = calamine::Range::new((0, 0), (3, 1));
+        for row in 0..4 {
+            range.set_value((row, 0), calamine::Data::Bool(row % 2 == 0));
+            range.set_value((row, 1), calamine::Data::Error(calamine::CellErrorType::NA));
+        }
+        range.set_value((3, 1), calamine::Data::String("#DIV/0!".to_string()));
+
+        let sheet = analyze_sheet(
+            "Sheet1",
+            calamine::SheetVisible::Visible,
+            &range,
+            &[],
+            &ClassifierConfig::default(),
+        );
+        assert_eq!(sheet.data_cells, 8);
+        assert_eq!(sheet.density, 1.0);
+        assert_eq!(sheet.error_ratio, 0.5);
+
+        let bools = &sheet.column_data_types[0];
+        assert_eq!((bools.bool_count, bools.text_count), (4, 0));
+        assert_eq!(bools.bool_type_ratio, 1.0);
+
+        let errors = &sheet.column_data_types[1];
+        assert_eq!(errors.error_count, 4);
+        assert_eq!(errors.error_kinds.get("#N/A"), Some(&3));
+        assert_eq!(errors.error_kinds.get("#DIV/0!"), Some(&1));
+    }
+
+    #[test]
+    fn test_fixture_classification() {
+        // 默认配置下各示例工作簿的分类和数值单元格个数与原有的数值识别一致，
+        // 只有新支持的写法会增加数值单元格（test1_form 中的 "￥16,000"）
+        let expected = [
+            ("tests/test1_data.xlsx", SheetType::Data, 11),
+            ("tests/test1_form.xlsx", SheetType::Form, 4),
+            ("tests/test2_data.xlsx", SheetType::Data, 3),
+            ("tests/test2_form.xlsx", SheetType::Form, 9),
+            ("tests/test3_form.xlsx", SheetType::Form, 17),
+        ];
+        for (path, sheet_type, numeric_count) in expected {
+            let sheets = classify_excel_sheets(path).unwrap();
+            assert_eq!(sheets.len(), 1, "{}", path);
+            assert_eq!(sheets[0].sheet_type, sheet_type, "{}", path);
+            let numeric: u32 = sheets[0]
+                .column_data_types
+                .iter()
+                .map(|column| column.numeric_count)
+                .sum();
+            assert_eq!(numeric, numeric_count, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_open_workbook_by_content() {
+        // 扩展名与实际格式不符时应按文件内容识别，结果与原文件一致
+        let expected = classify_excel_sheets("tests/test1_data.xlsx").unwrap();
+
+        for ext in ["xls", "xlsm", "bin"] {
+            let path = std::env::temp_dir().join(format!(
+                "layout_view_{}_test1_data.{}",
+                std::process::id(),
+                ext
+            ));
+            std::fs::copy("tests/test1_data.xlsx", &path).unwrap();
+            let result = classify_excel_sheets(path.to_str().unwrap());
+            let _ = std::fs::remove_file(&path);
+
+            let result = result.unwrap();
+            assert_eq!(
+                serde_json::to_string(&result).unwrap(),
+                serde_json::to_string(&expected).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_from_bytes_and_reader() {
+        let expected = classify_excel_sheets("tests/test1_form.xlsx").unwrap();
+        let expected = serde_json::to_string(&expected).unwrap();
+
+        let config = ClassifierConfig::default();
+        let bytes = std::fs::read("tests/test1_form.xlsx").unwrap();
+        let from_bytes = classify_excel_sheets_from_bytes(&bytes, &config).unwrap();
+        assert_eq!(serde_json::to_string(&from_bytes).unwrap(), expected);
+
+        let file = File::open("tests/test1_form.xlsx").unwrap();
+        let from_reader = classify_excel_sheets_from_reader(BufReader::new(file), &config).unwrap();
+        assert_eq!(serde_json::to_string(&from_reader).unwrap(), expected);
+
+        assert!(classify_excel_sheets_from_bytes(b"not a workbook", &config).is_err());
+    }
+
+    #[test]
+    fn test_cell_reference() {
+        assert_eq!(cell_reference(0, 0), "A1");
+        assert_eq!(cell_reference(9, 25), "Z10");
+        assert_eq!(cell_reference(0, 26), "AA1");
+        assert_eq!(cell_reference(2, 701), "ZZ3");
+        assert_eq!(cell_reference(2, 702), "AAA3");
+    }
+
+    #[test]
+    fn test_extract_form_fields_from_file() {
+        let sheets = extract_form_fields("tests/test1_form.xlsx").unwrap();
+        assert_eq!(sheets.len(), 1);
+
+        let fields = &sheets[0].fields;
+        let find = |label_cell: &str| fields.iter().find(|f| f.label_cell == label_cell);
+
+        let name = find("A4").unwrap();
+        assert_eq!(name.label, "姓名 Chinese Name *");
+        assert_eq!(name.value, "严晴（测试）");
+        assert_eq!(name.value_cell, "B4");
+
+        // 合并单元格导致标签与值之间隔着空白列
+        assert_eq!(find("A10").unwrap().value_cell, "D10");
+        // 日期按 ISO 格式输出
+        assert_eq!(find("C5").unwrap().value, "2002-01-02");
+        // 空白字段右侧是另一个标签，不应配对
+        assert!(find("A21").is_none());
+    }
+
+    #[test]
+    fn test_extract_records_from_file() {
+        let mut records = Vec::new();
+        extract_records("tests/test1_data.xlsx", |sheet_name, record| {
+            records.push((sheet_name.to_string(), record))
+        })
+        .unwrap();
+
+        assert_eq!(records.len(), 4);
+        let (sheet_name, first) = &records[0];
+        assert_eq!(sheet_name, "Sheet1");
+        assert_eq!(first.row, 3);
+        assert!(first.values.contains_key("姓名"));
+        assert_eq!(first.values.len(), 18);
+    }
+
+    #[test]
+    fn test_classify_with_config() {
+        let default = classify_excel_sheets("tests/test1_form.xlsx").unwrap();
+        let tuned = classify_excel_sheets_with_config(
+            "tests/test1_form.xlsx",
+            &ClassifierConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_string(&default).unwrap(),
+            serde_json::to_string(&tuned).unwrap()
+        );
+
+        // 降低极高密度阈值后，密度 0.598 的表单会被判为数据表
+        let config = ClassifierConfig {
+            high_density: 0.5,
+            ..ClassifierConfig::default()
+        };
+        let tuned = classify_excel_sheets_with_config("tests/test1_form.xlsx", &config).unwrap();
+        assert_eq!(default[0].sheet_type, SheetType::Form);
+        assert_eq!(tuned[0].sheet_type, SheetType::Data);
+    }
+
+    #[test]
+    fn test_include_hidden_sheets() {
+        // test2_form.xlsx 中除第一个工作表外都是隐藏工作表
+        let visible = calculate_sheet_density("tests/test2_form.xlsx").unwrap();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].visible, "Visible");
+
+        let config = ClassifierConfig {
+            visibility: SheetVisibility::ALL.to_vec(),
+            ..ClassifierConfig::default()
+        };
+        let all = calculate_sheet_density_with_config("tests/test2_form.xlsx", &config).unwrap();
+        assert_eq!(all.len(), 4);
+        assert!(all[1..].iter().all(|sheet| sheet.visible == "Hidden"));
+
+        let config = ClassifierConfig {
+            visibility: vec![SheetVisibility::Hidden],
+            ..ClassifierConfig::default()
+        };
+        let hidden = classify_excel_sheets_with_config("tests/test2_form.xlsx", &config).unwrap();
+        assert!(!hidden.is_empty());
+        assert!(hidden.iter().all(|sheet| sheet.visible == "Hidden"));
+    }
+
+    #[test]
+    fn test_select_sheets_by_name() {
+        let config = ClassifierConfig {
+            visibility: SheetVisibility::ALL.to_vec(),
+            ..ClassifierConfig::default()
+        };
+        let all = calculate_sheet_density_with_config("tests/test2_form.xlsx", &config).unwrap();
+        let config = ClassifierConfig {
+            sheets: vec![all[2].sheet_name.clone()],
+            ..config
+        };
+        let selected =
+            calculate_sheet_density_with_config("tests/test2_form.xlsx", &config).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].sheet_name, all[2].sheet_name);
+
+        // 名称不存在时没有工作表被读取
+        let config = ClassifierConfig {
+            sheets: vec!["no such sheet".to_string()],
+            ..config
+        };
+        let mut count = 0;
+        extract_records_with_config("tests/test1_data.xlsx", &config, |_, _| count += 1).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_merged_cells() {
+        // 4 行 4 列的表单：第 0 行标题合并整行，下面每行标签占 1 列、填写栏合并 3 列
+        let mut range = calamine::Range::new((0, 0), (3, 3));
+        range.set_value((0, 0), calamine::Data::String("申请表".to_string()));
+        let mut merged_regions = vec![MergedRegion {
+            first_row: 0,
+            first_col: 0,
+            end_row: 0,
+            end_col: 3,
+        }];
+        for row in 1..4 {
+            range.set_value((row, 0), calamine::Data::String(format!("标签{}", row)));
+            range.set_value((row, 1), calamine::Data::String("填写".to_string()));
+            merged_regions.push(MergedRegion {
+                first_row: row,
+                first_col: 1,
+                end_row: row,
+                end_col: 3,
+            });
+        }
+        let analyze = |density_mode| {
+            let config = ClassifierConfig {
+                density_mode,
+                ..ClassifierConfig::default()
+            };
+            let sheet = analyze_sheet(
+                "Sheet1",
+                calamine::SheetVisible::Visible,
+                &range,
+                &merged_regions,
+                &config,
+            );
+            (classify_sheet_with_config(&sheet, &config), sheet)
+        };
+
+        let (classified, sheet) = analyze(DensityMode::Cells);
+        assert_eq!(sheet.data_cells, 7);
+        assert_eq!(sheet.merged_cells, 13);
+        assert_eq!(sheet.merged_regions.len(), 4);
+        assert_eq!(classified.sheet_type, SheetType::Form);
+        assert_eq!(classified.explanation.matched_rule, "merged_form");
+
+        // 合并区域覆盖的单元格计为有数据后密度变高，但仍按合并单元格判为表单
+        let (classified, sheet) = analyze(DensityMode::MergedAsFilled);
+        assert_eq!(sheet.data_cells, 16);
+        assert_eq!(sheet.density, 1.0);
+        assert_eq!(classified.sheet_type, SheetType::Form);
+    }
+
+    #[test]
+    fn test_classify_crosstab() {
+        // 地区 × 月份的数值报表：基本类型为 Data，细分为交叉表并带出结构
+        let months = ["1月", "2月", "3月", "4月"];
+        let regions = ["华东", "华南", "华北", "西南", "东北"];
+        let mut range = calamine::Range::new((0, 0), (5, 4));
+        range.set_value((0, 0), calamine::Data::String("地区".to_string()));
+        for (col, month) in months.iter().enumerate() {
+            range.set_value(
+                (0, col as u32 + 1),
+                calamine::Data::String(month.to_string()),
+            );
+        }
+        for (row, region) in regions.iter().enumerate() {
+            let row = row as u32 + 1;
+            range.set_value((row, 0), calamine::Data::String(region.to_string()));
+            for col in 1..=4 {
+                range.set_value((row, col), calamine::Data::Float((row * col) as f64 + 0.5));
+            }
+        }
+        let config = ClassifierConfig::default();
+        let sheet = analyze_sheet(
+            "Sheet1",
+            calamine::SheetVisible::Visible,
+            &range,
+            &[],
+            &config,
+        );
+        let classified = classify_sheet_with_config(&sheet, &config);
+        assert_eq!(classified.sheet_type, SheetType::Data);
+        assert_eq!(classified.layout_type, Some(LayoutType::Crosstab));
+
+        let layout = classified.crosstab.unwrap();
+        assert_eq!(layout.row_header_cols, vec![0]);
+        assert_eq!(layout.column_header_rows, vec![0]);
+        assert_eq!(layout.row_key_names, vec!["地区"]);
+        let records: Vec<Record> = unpivot_range(&range, &layout).collect();
+        assert_eq!(records.len(), 20);
+        assert_eq!(
+            serde_json::Value::Object(records[1].values.clone()),
+            serde_json::json!({"地区": "华东", "month": "2月", "value": 2.5})
+        );
+    }
+
+    #[test]
+    fn test_classify_three_column_crosstab() {
+        // 只有两个年份列的交叉表，首列也符合代码对照表的条件，应判为交叉表
+        let mut range = calamine::Range::new((0, 0), (5, 2));
+        for (col, label) in ["地区", "2023", "2024"].iter().enumerate() {
+            range.set_value((0, col as u32), calamine::Data::String(label.to_string()));
+        }
+        for (row, region) in ["华东", "华南", "华北", "西南", "东北"].iter().enumerate() {
+            let row = row as u32 + 1;
+            range.set_value((row, 0), calamine::Data::String(region.to_string()));
+            for col in 1..=2 {
+                range.set_value((row, col), calamine::Data::Float((row * col) as f64 + 0.5));
+            }
+        }
+        let config = ClassifierConfig::default();
+        let sheet = analyze_sheet(
+            "Sheet1",
+            calamine::SheetVisible::Visible,
+            &range,
+            &[],
+            &config,
+        );
+        let classified = classify_sheet_with_config(&sheet, &config);
+        assert_eq!(classified.sheet_type, SheetType::Data);
+        assert_eq!(classified.layout_type, Some(LayoutType::Crosstab));
+        assert_eq!(classified.explanation.layout_rule, "crosstab");
+
+        // 没有 layout_type 字段的旧版 JSON 按没有细分类型读取，基本类型不受影响
+        let mut json = serde_json::to_value(&classified).unwrap();
+        json.as_object_mut().unwrap().remove("layout_type");
+        let old: ClassifiedSheet = serde_json::from_value(json).unwrap();
+        assert_eq!(old.sheet_type, SheetType::Data);
+        assert_eq!(old.layout_type, None);
+    }
+
+    #[test]
+    fn test_read_merged_regions_from_file() {
+        let sheets = calculate_sheet_density("tests/test3_form.xlsx").unwrap();
+        assert!(!sheets[0].merged_regions.is_empty());
+        assert!(sheets[0].merged_cell_ratio > 0.5);
+    }
+
+    #[test]
+    fn test_analyze_sheet_sampling() {
+        // 前 120 行是单列说明文字，下面接 200 行 5 列的数值表
+        let mut range = calamine::Range::new((0, 0), (319, 4));
+        for row in 0..120 {
+            range.set_value((row, 0), calamine::Data::String(format!("说明 {}", row)));
+        }
+        for row in 120..320 {
+            for col in 0..5 {
+                range.set_value((row, col), calamine::Data::Int(row as i64));
+            }
+        }
+        let analyze = |strategy, sample_rows| {
+            let config = ClassifierConfig {
+                sampling: SamplingConfig {
+                    strategy,
+                    sample_rows,
+                },
+                ..ClassifierConfig::default()
+            };
+            analyze_sheet(
+                "Sheet1",
+                calamine::SheetVisible::Visible,
+                &range,
+                &[],
+                &config,
+            )
+        };
+
+        let head = analyze(SamplingStrategy::Head, 100);
+        assert_eq!(head.sampled_rows, 100);
+        assert_eq!(head.total_cells, 500);
+        assert!((head.density - 0.2).abs() < 1e-9);
+
+        let full = analyze(SamplingStrategy::Full, 100);
+        assert_eq!(full.sampled_rows, 320);
+        assert_eq!(full.sampling_strategy, SamplingStrategy::Full);
+        assert!(full.density > 0.6);
+
+        let strided = analyze(SamplingStrategy::Strided, 50);
+        assert_eq!(strided.sampled_rows, 50);
+        assert!(strided.density > 0.6);
+        assert_eq!(strided.last_row_first_col_content, Some("319".to_string()));
+
+        let head_tail = analyze(SamplingStrategy::HeadTail, 100);
+        assert_eq!(head_tail.sampled_rows, 100);
+        assert!(head_tail.density > head.density);
+    }
 }
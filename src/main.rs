@@ -1,18 +1,196 @@
-use layout_view::classify_excel_sheets;
+// wasm32 没有命令行和文件系统，只通过 `wasm` feature 的 JS 接口使用，命令行程序编译为空
+#![cfg_attr(target_arch = "wasm32", no_main)]
+#![cfg(not(target_arch = "wasm32"))]
+
+use layout_view::{
+    classify_excel_sheets_with_config, classify_workbooks_with_config,
+    extract_form_fields_with_config, extract_records_with_config, is_glob_pattern,
+    unpivot_crosstabs_with_config, ClassifierConfig, LayoutError, Record, SheetVisibility,
+};
+use serde::Serialize;
 use std::env;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 use std::process;
 
+const SUBCOMMANDS: [&str; 3] = ["form", "extract", "unpivot"];
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let mut config = take_config(&mut args);
+    take_visibility(&mut args, &mut config);
+    take_threads(&mut args, &mut config);
+    let batch = take_flag(&mut args, "--batch");
 
-    if args.len() != 2 {
-        eprintln!("Usage: {} <xlsx_file_path>", args[0]);
-        process::exit(1);
+    match args.as_slice() {
+        [_, command, xlsx_path] if command == "form" => {
+            print_json(extract_form_fields_with_config(xlsx_path, &config));
+        }
+        [_, command, xlsx_path, rest @ ..] if command == "extract" && rest.len() <= 1 => {
+            if !rest.is_empty() {
+                config.sheets = rest.to_vec();
+            }
+            print_records(|on_record| extract_records_with_config(xlsx_path, &config, on_record));
+        }
+        [_, command, xlsx_path, rest @ ..] if command == "unpivot" && rest.len() <= 1 => {
+            if !rest.is_empty() {
+                config.sheets = rest.to_vec();
+            }
+            print_records(|on_record| unpivot_crosstabs_with_config(xlsx_path, &config, on_record));
+        }
+        [_, xlsx_path] if !batch && !is_batch_input(xlsx_path) => {
+            print_json(classify_excel_sheets_with_config(xlsx_path, &config));
+        }
+        [_, inputs @ ..] if !inputs.is_empty() && !SUBCOMMANDS.contains(&inputs[0].as_str()) => {
+            print_batch(inputs, &config);
+        }
+        _ => usage(&args[0]),
+    }
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} [options] <workbook_path>", program);
+    eprintln!(
+        "       {} [options] <path|directory|glob>...  (batch mode, JSON Lines)",
+        program
+    );
+    eprintln!("       {} [options] form <workbook_path>", program);
+    eprintln!(
+        "       {} [options] extract <workbook_path> [sheet_name]",
+        program
+    );
+    eprintln!(
+        "       {} [options] unpivot <workbook_path> [sheet_name]",
+        program
+    );
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --config <file>         Load classifier thresholds from a TOML/JSON file");
+    eprintln!("  --include-hidden        Also analyze hidden and very hidden sheets");
+    eprintln!("  --visibility <list>     Only analyze sheets with the given visibility");
+    eprintln!("                          (comma-separated: visible,hidden,very_hidden)");
+    eprintln!("  --threads <n>           Number of worker threads (0 = one per CPU core)");
+    eprintln!("  --batch                 Use batch output (one JSON line per workbook)");
+    eprintln!("                          even for a single workbook");
+    process::exit(1);
+}
+
+/// 从参数中取出 `--config <file>`，加载分类阈值配置；未指定时使用默认配置
+fn take_config(args: &mut Vec<String>) -> ClassifierConfig {
+    let Some(pos) = args.iter().position(|arg| arg == "--config") else {
+        return ClassifierConfig::default();
+    };
+    if pos + 1 >= args.len() {
+        usage(&args[0]);
+    }
+
+    let path = args.remove(pos + 1);
+    args.remove(pos);
+    match ClassifierConfig::from_file(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error loading config {}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// 从参数中取出 `--include-hidden` 和 `--visibility <list>`，设置参与分析的工作表可见性
+fn take_visibility(args: &mut Vec<String>, config: &mut ClassifierConfig) {
+    if let Some(pos) = args.iter().position(|arg| arg == "--include-hidden") {
+        args.remove(pos);
+        config.visibility = SheetVisibility::ALL.to_vec();
+    }
+
+    let Some(pos) = args.iter().position(|arg| arg == "--visibility") else {
+        return;
+    };
+    if pos + 1 >= args.len() {
+        usage(&args[0]);
+    }
+
+    let list = args.remove(pos + 1);
+    args.remove(pos);
+    let visibility: Option<Vec<SheetVisibility>> = list
+        .split(',')
+        .map(|name| SheetVisibility::from_name(name.trim()))
+        .collect();
+    match visibility {
+        Some(visibility) if !visibility.is_empty() => config.visibility = visibility,
+        _ => {
+            eprintln!("Invalid visibility list: {}", list);
+            process::exit(1);
+        }
+    }
+}
+
+/// 从参数中取出 `--threads <n>`，设置并行分析的线程数
+fn take_threads(args: &mut Vec<String>, config: &mut ClassifierConfig) {
+    let Some(pos) = args.iter().position(|arg| arg == "--threads") else {
+        return;
+    };
+    if pos + 1 >= args.len() {
+        usage(&args[0]);
+    }
+
+    let value = args.remove(pos + 1);
+    args.remove(pos);
+    match value.parse() {
+        Ok(threads) => config.threads = threads,
+        Err(_) => {
+            eprintln!("Invalid thread count: {}", value);
+            process::exit(1);
+        }
     }
+}
+
+/// 从参数中取出不带值的开关，返回是否出现
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|arg| arg == flag) {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 目录或通配符按批量模式处理
+fn is_batch_input(input: &str) -> bool {
+    is_glob_pattern(input) || Path::new(input).is_dir()
+}
+
+/// 批量模式：每个工作簿输出一行 JSON（`{"path", "sheets"}` 或 `{"path", "error"}`），
+/// 单个文件出错不中断，全部处理完后在标准错误输出汇总
+fn print_batch(inputs: &[String], config: &ClassifierConfig) {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut write_error = None;
+    let (mut total, mut failed) = (0, 0);
+
+    classify_workbooks_with_config(inputs, config, |result| {
+        total += 1;
+        failed += result.error.is_some() as usize;
+        if write_error.is_some() {
+            return;
+        }
+        let written = serde_json::to_string(&result)
+            .map_err(io::Error::from)
+            .and_then(|line| writeln!(out, "{}", line).and_then(|_| out.flush()));
+        if let Err(e) = written {
+            write_error = Some(e);
+        }
+    });
 
-    let xlsx_path = &args[1];
+    if let Some(e) = write_error {
+        eprintln!("Error writing results: {}", e);
+        process::exit(1);
+    }
+    eprintln!("Processed {} workbooks, {} failed", total, failed);
+}
 
-    match classify_excel_sheets(xlsx_path) {
+fn print_json<T: Serialize>(result: Result<T, LayoutError>) {
+    match result {
         Ok(results) => match serde_json::to_string(&results) {
             Ok(json_output) => println!("{}", json_output),
             Err(e) => {
@@ -21,8 +199,42 @@ fn main() {
             }
         },
         Err(e) => {
-            eprintln!("Error processing XLSX file: {}", e);
+            eprintln!("Error processing workbook: {}", e);
             process::exit(1);
         }
     }
 }
+
+/// 以 JSON Lines 格式逐行输出记录（Data 工作表的记录或交叉表逆透视后的记录），每行一条记录
+/// `source` 把生成的每条记录交给传入的回调
+fn print_records<S>(source: S)
+where
+    S: FnOnce(&mut dyn FnMut(&str, Record)) -> Result<(), LayoutError>,
+{
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut write_error = None;
+
+    let result = source(&mut |sheet_name, record| {
+        if write_error.is_some() {
+            return;
+        }
+        let line = serde_json::json!({
+            "sheet_name": sheet_name,
+            "row": record.row,
+            "record": record.values,
+        });
+        if let Err(e) = writeln!(out, "{}", line) {
+            write_error = Some(e);
+        }
+    });
+
+    if let Err(e) = result {
+        eprintln!("Error processing workbook: {}", e);
+        process::exit(1);
+    }
+    if let Some(e) = write_error.or_else(|| out.flush().err()) {
+        eprintln!("Error writing records: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,128 @@
+//! 合并单元格
+//!
+//! 表单大量使用合并单元格：一个合并区域只有左上角单元格有值，其余单元格在数据中为空，
+//! 会拉低密度。这里读取工作簿中的合并区域（.xlsx/.xlsm 和 .xls 支持，其他格式视为没有合并），
+//! 统计数据块中被合并区域覆盖的单元格，并可按配置把这些单元格计为有数据。
+
+use calamine::{Reader, Sheets};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+
+/// 一个合并区域，行列号从 0 开始，包含首尾
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergedRegion {
+    pub first_row: u32,
+    pub first_col: u32,
+    pub end_row: u32,
+    pub end_col: u32,
+}
+
+/// 计算密度时如何对待合并单元格
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DensityMode {
+    /// 只统计非空单元格，合并区域中除左上角外的单元格计为空白
+    #[default]
+    Cells,
+    /// 被合并区域覆盖的单元格都计为有数据
+    MergedAsFilled,
+}
+
+impl MergedRegion {
+    pub(crate) fn contains(&self, row: u32, col: u32) -> bool {
+        (self.first_row..=self.end_row).contains(&row)
+            && (self.first_col..=self.end_col).contains(&col)
+    }
+}
+
+impl From<&calamine::Dimensions> for MergedRegion {
+    fn from(dimensions: &calamine::Dimensions) -> Self {
+        MergedRegion {
+            first_row: dimensions.start.0,
+            first_col: dimensions.start.1,
+            end_row: dimensions.end.0,
+            end_col: dimensions.end.1,
+        }
+    }
+}
+
+/// 读取工作簿中每个工作表的合并区域（工作表名称 -> 合并区域）
+/// 读取失败或格式不支持时返回空表，不影响其他分析
+pub(crate) fn read_merged_regions<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+) -> HashMap<String, Vec<MergedRegion>> {
+    let mut result: HashMap<String, Vec<MergedRegion>> = HashMap::new();
+    match workbook {
+        Sheets::Xlsx(xlsx) => {
+            if xlsx.load_merged_regions().is_err() {
+                return result;
+            }
+            for name in xlsx.sheet_names() {
+                let regions = xlsx
+                    .merged_regions_by_sheet(&name)
+                    .into_iter()
+                    .map(|(_, _, dimensions)| MergedRegion::from(dimensions))
+                    .collect();
+                result.insert(name, regions);
+            }
+        }
+        Sheets::Xls(xls) => {
+            for name in xls.sheet_names() {
+                if let Some(dimensions) = xls.worksheet_merge_cells(&name) {
+                    result.insert(name, dimensions.iter().map(MergedRegion::from).collect());
+                }
+            }
+        }
+        _ => {}
+    }
+    result
+}
+
+/// 数据块内被合并区域覆盖的单元格（行号, 列号），每个合并区域只遍历一次
+/// `bounds` 为 (起始行, 起始列, 结束行, 结束列)
+pub(crate) fn covered_cells(
+    merged_regions: &[MergedRegion],
+    bounds: (u32, u32, u32, u32),
+) -> HashSet<(u32, u32)> {
+    let (start_row, start_col, end_row, end_col) = bounds;
+    let mut covered = HashSet::new();
+    for region in merged_regions {
+        let rows = region.first_row.max(start_row)..=region.end_row.min(end_row);
+        let cols = region.first_col.max(start_col)..=region.end_col.min(end_col);
+        for row in rows {
+            covered.extend(cols.clone().map(|col| (row, col)));
+        }
+    }
+    covered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_covered_cells() {
+        let regions = [
+            MergedRegion {
+                first_row: 0,
+                first_col: 0,
+                end_row: 0,
+                end_col: 3,
+            },
+            MergedRegion {
+                first_row: 2,
+                first_col: 1,
+                end_row: 5,
+                end_col: 2,
+            },
+        ];
+        // 第 0 行被覆盖 4 个单元格中的 3 个（列范围 1..=3），第 2、3 行各 2 个
+        let covered = covered_cells(&regions, (0, 1, 3, 3));
+        assert_eq!(covered.len(), 7);
+        assert!(covered.contains(&(0, 1)) && covered.contains(&(3, 2)));
+        assert!(!covered.contains(&(0, 0)) && !covered.contains(&(4, 1)));
+        assert!(covered_cells(&regions, (1, 0, 1, 3)).is_empty());
+        assert!(covered_cells(&[], (0, 0, 1, 3)).is_empty());
+    }
+}
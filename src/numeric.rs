@@ -0,0 +1,225 @@
+//! 数值字符串识别
+//!
+//! 除整数、小数、千分位数和百分数外，还识别：
+//! - 货币符号和货币代码（"¥1,234.00"、"$12"、"1,234 CNY"、"12元"）
+//! - 会计格式负数（"(1,234)"）
+//! - 科学计数法（"1.2E+05"）
+//! - 全角数字和符号（"１２３"、"－１．５"）
+//! - 以逗号作小数点的写法（"1.234,56"、"12,5"），是否接受由 `NumberLocale` 决定
+//! - 中文数量单位（"12万"、"3.5亿元"）
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 数值字符串的小数点约定
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberLocale {
+    /// 两种约定都接受，"12,5"、"1 234" 这类写法也按数值处理
+    Auto,
+    /// 小数点为 "."，千分位为 ","（中文、英文）
+    #[default]
+    #[serde(alias = "zh", alias = "en")]
+    DecimalPoint,
+    /// 小数点为 ","，千分位为 "."、空格或 "'"（德语、法语等）
+    #[serde(alias = "de", alias = "fr")]
+    DecimalComma,
+}
+
+// 货币符号，可出现在数字前或数字后
+const CURRENCY_SYMBOLS: [&str; 13] = [
+    "US$", "HK$", "NT$", "R$", "¥", "￥", "$", "€", "£", "₩", "₹", "₽", "元",
+];
+// 货币代码，可出现在数字前或数字后，与数字之间可以有空格
+const CURRENCY_CODES: [&str; 16] = [
+    "CNY", "RMB", "USD", "EUR", "GBP", "JPY", "HKD", "TWD", "KRW", "SGD", "AUD", "CAD", "CHF",
+    "INR", "RUB", "MOP",
+];
+// 以字符串保存、达到该位数的纯数字串视为编码（手机号为 11 位）
+const MIN_CODE_DIGITS: usize = 11;
+// 中文数量单位
+const MAGNITUDE_UNITS: [char; 6] = ['万', '亿', '萬', '億', '千', '百'];
+
+lazy_static! {
+    static ref DECIMAL_POINT_RE: Regex =
+        Regex::new(r"^(?:\d+\.?\d*|\.\d+|\d{1,3}(?:,\d{3})+(?:\.\d+)?)$").unwrap();
+    static ref DECIMAL_COMMA_RE: Regex =
+        Regex::new(r"^(?:\d+,?\d*|,\d+|\d{1,3}(?:[. '\u{a0}]\d{3})+(?:,\d+)?)$").unwrap();
+    static ref SCIENTIFIC_POINT_RE: Regex =
+        Regex::new(r"^(?:\d+\.?\d*|\.\d+)[eE][+-]?\d+$").unwrap();
+    static ref SCIENTIFIC_COMMA_RE: Regex = Regex::new(r"^(?:\d+,?\d*|,\d+)[eE][+-]?\d+$").unwrap();
+}
+
+/// 数值字符串的附加写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct NumericForm {
+    pub(crate) currency: bool, // 带货币符号或货币代码
+    pub(crate) percent: bool,  // 百分数或千分数
+}
+
+/// 检查字符串是否为数值型数据
+pub(crate) fn is_numeric_string(s: &str, locale: NumberLocale) -> bool {
+    parse_numeric_string(s, locale).is_some()
+}
+
+/// 识别数值字符串及其写法，不是数值时返回 None
+pub(crate) fn parse_numeric_string(s: &str, locale: NumberLocale) -> Option<NumericForm> {
+    let normalized = to_half_width(s);
+    let mut s = normalized.trim();
+    let mut form = NumericForm::default();
+
+    // 会计格式负数：(1,234)
+    if let Some(inner) = s.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        s = inner.trim();
+    }
+
+    // 前缀：正负号和货币，顺序不限（"-¥12"、"¥-12"）
+    let (rest, signed) = strip_sign(s);
+    let (rest, currency) = strip_currency_prefix(rest);
+    s = rest;
+    form.currency |= currency;
+    if !signed {
+        s = strip_sign(s).0;
+    }
+
+    // 后缀：货币、数量单位、百分号
+    let (rest, currency) = strip_currency_suffix(s);
+    s = rest;
+    form.currency |= currency;
+    if let Some(rest) = s.strip_suffix(MAGNITUDE_UNITS) {
+        s = rest.trim_end();
+    } else if let Some(rest) = s.strip_suffix(['%', '‰']) {
+        s = rest.trim_end();
+        form.percent = true;
+    }
+
+    if s.is_empty() {
+        return None;
+    }
+
+    let point = || DECIMAL_POINT_RE.is_match(s) || SCIENTIFIC_POINT_RE.is_match(s);
+    let comma = || DECIMAL_COMMA_RE.is_match(s) || SCIENTIFIC_COMMA_RE.is_match(s);
+    let matched = match locale {
+        NumberLocale::Auto => point() || comma(),
+        NumberLocale::DecimalPoint => point(),
+        NumberLocale::DecimalComma => comma(),
+    };
+    matched.then_some(form)
+}
+
+/// 编码形式的数字串：全部是数字，且长度不少于 11 位或以 0 开头
+pub(crate) fn is_code_string(s: &str) -> bool {
+    let s = to_half_width(s);
+    let s = s.trim();
+    s.len() >= 2
+        && s.chars().all(|c| c.is_ascii_digit())
+        && (s.len() >= MIN_CODE_DIGITS || s.starts_with('0'))
+}
+
+/// 检查单元格是否包含数值型数据
+pub(crate) fn is_numeric_cell(cell: &calamine::Data, locale: NumberLocale) -> bool {
+    match cell {
+        calamine::Data::Int(_) | calamine::Data::Float(_) => true,
+        calamine::Data::String(s) => is_numeric_string(s, locale),
+        _ => false,
+    }
+}
+
+/// 把全角数字、字母和符号转换为半角，全角空格转换为普通空格
+fn to_half_width(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+fn strip_sign(s: &str) -> (&str, bool) {
+    match s.strip_prefix(['-', '+', '−']) {
+        Some(rest) => (rest.trim_start(), true),
+        None => (s, false),
+    }
+}
+
+fn strip_currency_prefix(s: &str) -> (&str, bool) {
+    let symbol = CURRENCY_SYMBOLS
+        .iter()
+        .chain(CURRENCY_CODES.iter())
+        .find_map(|prefix| s.strip_prefix(prefix));
+    match symbol {
+        Some(rest) => (rest.trim_start(), true),
+        None => (s, false),
+    }
+}
+
+fn strip_currency_suffix(s: &str) -> (&str, bool) {
+    let symbol = CURRENCY_SYMBOLS
+        .iter()
+        .chain(CURRENCY_CODES.iter())
+        .find_map(|suffix| s.strip_suffix(suffix));
+    match symbol {
+        Some(rest) => (rest.trim_end(), true),
+        None => (s, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_numeric_strings() {
+        let auto = |s| is_numeric_string(s, NumberLocale::Auto);
+        assert!(auto("¥1,234.00"));
+        assert!(auto("-$12.5"));
+        assert!(auto("1,234 CNY"));
+        assert!(auto("USD 99"));
+        assert!(auto("(1,234)"));
+        assert!(auto("（１，２３４）"));
+        assert!(auto("1.2E+05"));
+        assert!(auto("１２３"));
+        assert!(auto("1.234,56"));
+        assert!(auto("12万"));
+        assert!(auto("3.5亿元"));
+        assert!(auto("12元"));
+        assert!(auto("5‰"));
+
+        assert!(!auto("¥"));
+        assert!(!auto("万"));
+        assert!(!auto("E5"));
+        assert!(!auto("12.34.56"));
+        assert!(!auto("USD"));
+        assert!(!auto("第12万"));
+
+        // 编码形式的数字串仍按数值统计，表头识别另行把它们当作取值
+        assert!(auto("00123"));
+        assert!(is_code_string("00123"));
+        assert!(is_code_string("13800000000"));
+        assert!(!is_code_string("0"));
+        assert_eq!(
+            parse_numeric_string("¥1,234.00", NumberLocale::Auto),
+            Some(NumericForm {
+                currency: true,
+                percent: false
+            })
+        );
+
+        // 按区域约定区分小数点
+        assert!(is_numeric_string("1,234.56", NumberLocale::DecimalPoint));
+        assert!(!is_numeric_string("1.234,56", NumberLocale::DecimalPoint));
+        assert!(is_numeric_string("1.234,56", NumberLocale::DecimalComma));
+        assert!(is_numeric_string("1 234,5", NumberLocale::DecimalComma));
+        assert!(!is_numeric_string("1,234.56", NumberLocale::DecimalComma));
+
+        // 默认约定与只认小数点的写法一致，"12,5"、"1 234" 不算数值
+        let default = |s| is_numeric_string(s, NumberLocale::default());
+        assert!(default("1,234.56"));
+        assert!(!default("12,5"));
+        assert!(!default("1 234"));
+        assert!(auto("12,5"));
+        assert!(auto("1 234"));
+    }
+}
@@ -0,0 +1,204 @@
+//! 并行处理
+//!
+//! 工作表之间、批量模式下工作簿之间的分析互不依赖，用固定数量的工作线程并行处理：
+//! 各线程从共享的序号中领取下一个任务，结果按任务序号重新排列后依次交出，
+//! 因此输出顺序与串行处理完全相同，不受线程数和完成先后影响。
+//! 领取任务时限制领先于已交出结果的数量，暂存的结果不会随任务数增长。
+
+use std::collections::BTreeMap;
+use std::sync::{mpsc, Condvar, Mutex, PoisonError};
+use std::thread;
+
+// 已领取但尚未交出的任务最多为线程数的倍数
+const WINDOW_PER_WORKER: usize = 2;
+
+/// 任务领取进度
+struct Progress {
+    next: usize,    // 下一个待领取的任务序号
+    emitted: usize, // 已按序交出的结果数
+    aborted: bool,  // 有线程 panic，停止领取
+}
+
+type SharedProgress = (Mutex<Progress>, Condvar);
+
+/// 线程 panic 时通知其他线程停止等待，避免它们等待永远不会交出的结果
+struct AbortOnPanic<'a>(&'a SharedProgress);
+
+impl Drop for AbortOnPanic<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let (lock, condvar) = self.0;
+            lock.lock().unwrap_or_else(PoisonError::into_inner).aborted = true;
+            condvar.notify_all();
+        }
+    }
+}
+
+/// 实际使用的线程数：`threads` 为 0 时取可用 CPU 数，且不超过任务数
+/// wasm32 不能创建线程，总是串行处理
+pub(crate) fn worker_count(threads: usize, jobs: usize) -> usize {
+    if cfg!(target_arch = "wasm32") {
+        return 1;
+    }
+    let threads = if threads == 0 {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        threads
+    };
+    threads.min(jobs).max(1)
+}
+
+/// 并行处理每个任务，按输入顺序返回结果
+pub(crate) fn parallel_map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let mut results = Vec::with_capacity(items.len());
+    for_each_ordered(items, threads, f, |result| results.push(result));
+    results
+}
+
+/// 并行处理每个任务，结果按输入顺序依次交给 `emit`：
+/// 前面的任务一完成就交出，不必等待全部任务结束
+pub(crate) fn for_each_ordered<T, R, F, C>(items: &[T], threads: usize, f: F, mut emit: C)
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    C: FnMut(R),
+{
+    let workers = worker_count(threads, items.len());
+    if workers <= 1 {
+        items.iter().for_each(|item| emit(f(item)));
+        return;
+    }
+
+    // 序号 i 只有在 i - 已交出数 < window 时才能领取，暂存的结果不超过 window 个
+    let window = workers * WINDOW_PER_WORKER;
+    let progress: SharedProgress = (
+        Mutex::new(Progress {
+            next: 0,
+            emitted: 0,
+            aborted: false,
+        }),
+        Condvar::new(),
+    );
+    let (sender, receiver) = mpsc::sync_channel(window);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (progress, f) = (&progress, &f);
+            scope.spawn(move || {
+                let _guard = AbortOnPanic(progress);
+                loop {
+                    let index = {
+                        let (lock, condvar) = progress;
+                        let mut state = condvar
+                            .wait_while(lock.lock().unwrap(), |state| {
+                                !state.aborted && state.next >= state.emitted + window
+                            })
+                            .unwrap();
+                        if state.aborted {
+                            break;
+                        }
+                        state.next += 1;
+                        state.next - 1
+                    };
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, f(item))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // 先完成的后续任务暂存起来，等前面的任务完成后按序交出
+        let _guard = AbortOnPanic(&progress);
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            let before = expected;
+            while let Some(result) = pending.remove(&expected) {
+                emit(result);
+                expected += 1;
+            }
+            if expected > before {
+                let (lock, condvar) = &progress;
+                lock.lock().unwrap().emitted = expected;
+                condvar.notify_all();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parallel_map_keeps_order() {
+        let items: Vec<u64> = (0..20).collect();
+        // 前面的任务耗时更长，完成顺序与输入顺序相反
+        let results = parallel_map(&items, 4, |&i| {
+            thread::sleep(Duration::from_millis(20 - i));
+            i * 10
+        });
+        assert_eq!(results, (0..20).map(|i| i * 10).collect::<Vec<_>>());
+
+        assert_eq!(worker_count(8, 3), 3);
+        assert_eq!(worker_count(2, 0), 1);
+        assert!(worker_count(0, 100) >= 1);
+    }
+
+    #[test]
+    fn test_for_each_ordered_bounds_pending_results() {
+        // 交出结果很慢时，已开始但未交出的任务不超过窗口大小
+        let items: Vec<usize> = (0..50).collect();
+        let started = AtomicUsize::new(0);
+        let emitted = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let mut results = Vec::new();
+        for_each_ordered(
+            &items,
+            3,
+            |&i| {
+                let in_flight =
+                    started.fetch_add(1, Ordering::SeqCst) + 1 - emitted.load(Ordering::SeqCst);
+                max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                i
+            },
+            |i| {
+                thread::sleep(Duration::from_millis(2));
+                results.push(i);
+                emitted.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+        assert_eq!(results, items);
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 3 * WINDOW_PER_WORKER);
+        assert_eq!(worker_count(2, 0), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_for_each_ordered_propagates_panic() {
+        // 第一个任务 panic 后，等待窗口的线程不能一直阻塞
+        let items: Vec<usize> = (0..50).collect();
+        for_each_ordered(
+            &items,
+            3,
+            |&i| {
+                assert!(i != 0, "任务失败");
+                i
+            },
+            |_| {},
+        );
+    }
+}
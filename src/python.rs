@@ -0,0 +1,584 @@
+//! Python 扩展模块（`python` feature）
+//!
+//! 用 pyo3 把分类、密度计算和提取接口导出为 Python 模块 `layout_view`：
+//! - 工作簿可以是路径（`str` 或 `os.PathLike`）或内存中的 `bytes`/`bytearray`
+//! - 分析期间释放 GIL，其他 Python 线程可以同时运行
+//! - 分类结果以 `ClassifiedSheet`、`SheetDensity`、`ColumnDataTypeInfo` 对象返回，
+//!   嵌套较深的字段（分类依据、数据块、交叉表结构等）转换为 dict/list
+//! - 提取接口返回与命令行 JSON 输出相同结构的 dict，记录提取接口返回逐条产生记录的迭代器
+
+use crate::{ClassifierConfig, LayoutError, Record};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyBytes, PyString};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::thread;
+
+// 记录迭代器中后台线程领先于 Python 端的记录数
+const RECORD_BUFFER: usize = 256;
+
+create_exception!(
+    layout_view,
+    LayoutViewError,
+    PyException,
+    "无法识别、损坏或分析失败的工作簿"
+);
+
+impl From<LayoutError> for PyErr {
+    fn from(e: LayoutError) -> Self {
+        match e {
+            LayoutError::Io(e) => e.into(),
+            LayoutError::InvalidArgument(_) | LayoutError::Config(_) => {
+                PyValueError::new_err(e.to_string())
+            }
+            e => LayoutViewError::new_err(e.to_string()),
+        }
+    }
+}
+
+/// Python 传入的工作簿：路径或字节（字节先复制出来，释放 GIL 后不再访问 Python 对象）
+enum Source {
+    Path(String),
+    Bytes(Vec<u8>),
+}
+
+impl Source {
+    fn from_py(source: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(bytes) = source.cast::<PyBytes>() {
+            return Ok(Source::Bytes(bytes.as_bytes().to_vec()));
+        }
+        if let Ok(bytes) = source.cast::<PyByteArray>() {
+            return Ok(Source::Bytes(bytes.to_vec()));
+        }
+        let path: PathBuf = source.extract()?;
+        match path.into_os_string().into_string() {
+            Ok(path) => Ok(Source::Path(path)),
+            Err(path) => Err(PyValueError::new_err(format!(
+                "path is not valid UTF-8: {}",
+                path.to_string_lossy()
+            ))),
+        }
+    }
+}
+
+/// 解析 `config` 参数：dict 或 JSON 字符串，未给出的字段使用默认值
+fn config_from_py(config: Option<&Bound<'_, PyAny>>) -> PyResult<ClassifierConfig> {
+    let Some(config) = config.filter(|c| !c.is_none()) else {
+        return Ok(ClassifierConfig::default());
+    };
+    let json = if config.is_instance_of::<PyString>() {
+        config.extract::<String>()?
+    } else {
+        let dumps = config.py().import("json")?.getattr("dumps")?;
+        dumps.call1((config,))?.extract::<String>()?
+    };
+    Ok(ClassifierConfig::from_json_str(&json)?)
+}
+
+/// 把可序列化的值转换为 Python 的 dict/list 等内置类型
+fn to_py_object<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+    let json = serde_json::to_string(value).map_err(LayoutError::from)?;
+    let loads = py.import("json")?.getattr("loads")?;
+    Ok(loads.call1((json,))?.unbind())
+}
+
+/// 枚举按序列化名称转换为字符串（如 SheetType::Data -> "Data"）
+fn enum_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// 单列的数据类型分布
+#[pyclass(name = "ColumnDataTypeInfo", module = "layout_view", frozen)]
+pub struct PyColumnDataTypeInfo {
+    inner: crate::ColumnDataTypeInfo,
+}
+
+#[pymethods]
+impl PyColumnDataTypeInfo {
+    #[getter]
+    fn column_index(&self) -> u32 {
+        self.inner.column_index
+    }
+    #[getter]
+    fn numeric_count(&self) -> u32 {
+        self.inner.numeric_count
+    }
+    #[getter]
+    fn text_count(&self) -> u32 {
+        self.inner.text_count
+    }
+    #[getter]
+    fn date_count(&self) -> u32 {
+        self.inner.date_count
+    }
+    #[getter]
+    fn datetime_count(&self) -> u32 {
+        self.inner.datetime_count
+    }
+    #[getter]
+    fn time_count(&self) -> u32 {
+        self.inner.time_count
+    }
+    #[getter]
+    fn duration_count(&self) -> u32 {
+        self.inner.duration_count
+    }
+    #[getter]
+    fn bool_count(&self) -> u32 {
+        self.inner.bool_count
+    }
+    #[getter]
+    fn error_count(&self) -> u32 {
+        self.inner.error_count
+    }
+    #[getter]
+    fn total_count(&self) -> u32 {
+        self.inner.total_count
+    }
+    #[getter]
+    fn numeric_type_ratio(&self) -> f64 {
+        self.inner.numeric_type_ratio
+    }
+    #[getter]
+    fn date_type_ratio(&self) -> f64 {
+        self.inner.date_type_ratio
+    }
+    #[getter]
+    fn datetime_type_ratio(&self) -> f64 {
+        self.inner.datetime_type_ratio
+    }
+    #[getter]
+    fn time_type_ratio(&self) -> f64 {
+        self.inner.time_type_ratio
+    }
+    #[getter]
+    fn duration_type_ratio(&self) -> f64 {
+        self.inner.duration_type_ratio
+    }
+    #[getter]
+    fn bool_type_ratio(&self) -> f64 {
+        self.inner.bool_type_ratio
+    }
+    #[getter]
+    fn error_type_ratio(&self) -> f64 {
+        self.inner.error_type_ratio
+    }
+    #[getter]
+    fn error_kinds(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py_object(py, &self.inner.error_kinds)
+    }
+    #[getter]
+    fn semantic_type(&self) -> String {
+        enum_name(&self.inner.semantic_type)
+    }
+    #[getter]
+    fn semantic_ratio(&self) -> f64 {
+        self.inner.semantic_ratio
+    }
+    #[getter]
+    fn semantic_matches(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py_object(py, &self.inner.semantic_matches)
+    }
+
+    /// 转换为与 JSON 输出相同结构的 dict
+    fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py_object(py, &self.inner)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ColumnDataTypeInfo(column_index={}, total_count={}, semantic_type='{}')",
+            self.inner.column_index,
+            self.inner.total_count,
+            enum_name(&self.inner.semantic_type)
+        )
+    }
+}
+
+fn column_data_types(columns: &[crate::ColumnDataTypeInfo]) -> Vec<PyColumnDataTypeInfo> {
+    columns
+        .iter()
+        .map(|column| PyColumnDataTypeInfo {
+            inner: column.clone(),
+        })
+        .collect()
+}
+
+/// 生成 `SheetDensity` 和 `ClassifiedSheet` 的方法：两者共有字段的 getter 加上各自的方法
+macro_rules! sheet_methods {
+    ($class:ty { $($methods:tt)* }) => {
+        #[pymethods]
+        impl $class {
+            $($methods)*
+
+            #[getter]
+            fn sheet_name(&self) -> &str {
+                &self.inner.sheet_name
+            }
+            #[getter]
+            fn first_row(&self) -> u32 {
+                self.inner.first_row
+            }
+            #[getter]
+            fn first_col(&self) -> u32 {
+                self.inner.first_col
+            }
+            #[getter]
+            fn end_row(&self) -> u32 {
+                self.inner.end_row
+            }
+            #[getter]
+            fn end_col(&self) -> u32 {
+                self.inner.end_col
+            }
+            #[getter]
+            fn total_cells(&self) -> u32 {
+                self.inner.total_cells
+            }
+            #[getter]
+            fn data_cells(&self) -> u32 {
+                self.inner.data_cells
+            }
+            #[getter]
+            fn density(&self) -> f64 {
+                self.inner.density
+            }
+            #[getter]
+            fn visible(&self) -> &str {
+                &self.inner.visible
+            }
+            #[getter]
+            fn first_row_first_col_content(&self) -> Option<&str> {
+                self.inner.first_row_first_col_content.as_deref()
+            }
+            #[getter]
+            fn last_row_first_col_content(&self) -> Option<&str> {
+                self.inner.last_row_first_col_content.as_deref()
+            }
+            #[getter]
+            fn data_type_mix(&self) -> f64 {
+                self.inner.data_type_mix
+            }
+            #[getter]
+            fn column_data_types(&self) -> Vec<PyColumnDataTypeInfo> {
+                column_data_types(&self.inner.column_data_types)
+            }
+            #[getter]
+            fn error_ratio(&self) -> f64 {
+                self.inner.error_ratio
+            }
+            #[getter]
+            fn header_row(&self) -> Option<u32> {
+                self.inner.header_row
+            }
+            #[getter]
+            fn column_names(&self) -> Vec<String> {
+                self.inner.column_names.clone()
+            }
+            #[getter]
+            fn header_rows(&self) -> Vec<u32> {
+                self.inner.header_rows.clone()
+            }
+            #[getter]
+            fn header_tree(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+                to_py_object(py, &self.inner.header_tree)
+            }
+            #[getter]
+            fn row_type_consistency(&self) -> f64 {
+                self.inner.row_type_consistency
+            }
+            #[getter]
+            fn aspect_ratio(&self) -> f64 {
+                self.inner.aspect_ratio
+            }
+            #[getter]
+            fn sampling_strategy(&self) -> String {
+                enum_name(&self.inner.sampling_strategy)
+            }
+            #[getter]
+            fn sampled_rows(&self) -> u32 {
+                self.inner.sampled_rows
+            }
+            #[getter]
+            fn merged_cells(&self) -> u32 {
+                self.inner.merged_cells
+            }
+            #[getter]
+            fn merged_cell_ratio(&self) -> f64 {
+                self.inner.merged_cell_ratio
+            }
+            #[getter]
+            fn cell_density(&self) -> f64 {
+                self.inner.cell_density
+            }
+            #[getter]
+            fn shape_features(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+                to_py_object(py, &self.inner.shape_features)
+            }
+            #[getter]
+            fn crosstab(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+                to_py_object(py, &self.inner.crosstab)
+            }
+            #[getter]
+            fn merged_regions(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+                to_py_object(py, &self.inner.merged_regions)
+            }
+            #[getter]
+            fn regions(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+                to_py_object(py, &self.inner.regions)
+            }
+
+            /// 转换为与 JSON 输出相同结构的 dict
+            fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+                to_py_object(py, &self.inner)
+            }
+
+            /// 序列化为 JSON 字符串
+            fn to_json(&self) -> PyResult<String> {
+                Ok(serde_json::to_string(&self.inner).map_err(LayoutError::from)?)
+            }
+        }
+    };
+}
+
+/// 单个工作表的密度等指标（未分类）
+#[pyclass(name = "SheetDensity", module = "layout_view", frozen)]
+pub struct PySheetDensity {
+    inner: crate::SheetDataDensity,
+}
+
+sheet_methods!(PySheetDensity {
+    fn __repr__(&self) -> String {
+        format!(
+            "SheetDensity(sheet_name='{}', density={:.3})",
+            self.inner.sheet_name, self.inner.density
+        )
+    }
+});
+
+/// 单个工作表的分类结果
+#[pyclass(name = "ClassifiedSheet", module = "layout_view", frozen)]
+pub struct PyClassifiedSheet {
+    inner: crate::ClassifiedSheet,
+}
+
+sheet_methods!(PyClassifiedSheet {
+    #[getter]
+    fn sheet_type(&self) -> String {
+        enum_name(&self.inner.sheet_type)
+    }
+    #[getter]
+    fn layout_type(&self) -> Option<String> {
+        self.inner.layout_type.as_ref().map(enum_name)
+    }
+    #[getter]
+    fn classification_reason(&self) -> &str {
+        &self.inner.classification_reason
+    }
+    #[getter]
+    fn confidence(&self) -> f64 {
+        self.inner.confidence
+    }
+    #[getter]
+    fn explanation(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py_object(py, &self.inner.explanation)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ClassifiedSheet(sheet_name='{}', sheet_type='{}', layout_type={}, density={:.3})",
+            self.inner.sheet_name,
+            enum_name(&self.inner.sheet_type),
+            self.inner
+                .layout_type
+                .as_ref()
+                .map_or("None".to_string(), |t| format!("'{}'", enum_name(t))),
+            self.inner.density
+        )
+    }
+});
+
+/// 分类工作簿中的工作表
+#[pyfunction]
+#[pyo3(signature = (source, config = None))]
+fn classify_excel_sheets(
+    py: Python<'_>,
+    source: &Bound<'_, PyAny>,
+    config: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Vec<PyClassifiedSheet>> {
+    let source = Source::from_py(source)?;
+    let config = config_from_py(config)?;
+    let sheets = py.detach(|| match &source {
+        Source::Path(path) => crate::classify_excel_sheets_with_config(path, &config),
+        Source::Bytes(data) => crate::classify_excel_sheets_from_bytes(data, &config),
+    })?;
+    Ok(sheets
+        .into_iter()
+        .map(|inner| PyClassifiedSheet { inner })
+        .collect())
+}
+
+/// 计算工作簿中每个工作表的密度等指标
+#[pyfunction]
+#[pyo3(signature = (source, config = None))]
+fn calculate_sheet_density(
+    py: Python<'_>,
+    source: &Bound<'_, PyAny>,
+    config: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Vec<PySheetDensity>> {
+    let source = Source::from_py(source)?;
+    let config = config_from_py(config)?;
+    let sheets = py.detach(|| match &source {
+        Source::Path(path) => crate::calculate_sheet_density_with_config(path, &config),
+        Source::Bytes(data) => crate::calculate_sheet_density_from_bytes(data, &config),
+    })?;
+    Ok(sheets
+        .into_iter()
+        .map(|inner| PySheetDensity { inner })
+        .collect())
+}
+
+/// 提取 Form 类型工作表的键值对字段，返回 `[{"sheet_name", "fields": [...]}]`
+#[pyfunction]
+#[pyo3(signature = (source, config = None))]
+fn extract_form_fields(
+    py: Python<'_>,
+    source: &Bound<'_, PyAny>,
+    config: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Py<PyAny>> {
+    let source = Source::from_py(source)?;
+    let config = config_from_py(config)?;
+    let sheets = py.detach(|| match &source {
+        Source::Path(path) => crate::extract_form_fields_with_config(path, &config),
+        Source::Bytes(data) => crate::extract_form_fields_from_bytes(data, &config),
+    })?;
+    to_py_object(py, &sheets)
+}
+
+/// 工作表记录，与命令行 `extract`/`unpivot` 输出的每一行结构相同
+#[derive(Serialize)]
+struct SheetRecord {
+    sheet_name: String,
+    row: u32,
+    record: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 逐条返回记录的迭代器：后台线程提取记录，经有界通道逐条交给 Python，
+/// 不会把整个工作簿的记录放进一个列表；打开或读取工作簿的错误在迭代时抛出
+#[pyclass(name = "RecordIterator", module = "layout_view")]
+pub struct PyRecordIterator {
+    receiver: Mutex<mpsc::Receiver<Result<SheetRecord, LayoutError>>>,
+}
+
+#[pymethods]
+impl PyRecordIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<Py<PyAny>>> {
+        // 等待后台线程时释放 GIL
+        let message = py.detach(|| {
+            self.receiver
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .recv()
+        });
+        match message {
+            Ok(Ok(record)) => to_py_object(py, &record).map(Some),
+            Ok(Err(e)) => Err(e.into()),
+            // 后台线程结束，记录已全部返回
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// 在后台线程中运行记录提取，返回逐条读取记录的迭代器
+/// Python 端提前停止迭代时，剩余的记录直接丢弃
+fn spawn_records<F>(extract: F) -> PyRecordIterator
+where
+    F: FnOnce(&mut dyn FnMut(&str, Record)) -> Result<(), LayoutError> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(RECORD_BUFFER);
+    thread::spawn(move || {
+        let mut stopped = false;
+        let result = extract(&mut |sheet_name, record| {
+            if stopped {
+                return;
+            }
+            let record = SheetRecord {
+                sheet_name: sheet_name.to_string(),
+                row: record.row,
+                record: record.values,
+            };
+            stopped = sender.send(Ok(record)).is_err();
+        });
+        if let Err(e) = result {
+            let _ = sender.send(Err(e));
+        }
+    });
+    PyRecordIterator {
+        receiver: Mutex::new(receiver),
+    }
+}
+
+/// `sheet` 给出时只读取该工作表，其他工作表不会被加载
+fn config_with_sheet(mut config: ClassifierConfig, sheet: Option<String>) -> ClassifierConfig {
+    if let Some(sheet) = sheet {
+        config.sheets = vec![sheet];
+    }
+    config
+}
+
+/// 逐行提取 Data 类型工作表的记录，返回逐条产生 `{"sheet_name", "row", "record"}` 的迭代器
+#[pyfunction]
+#[pyo3(signature = (source, sheet = None, config = None))]
+fn extract_records(
+    source: &Bound<'_, PyAny>,
+    sheet: Option<String>,
+    config: Option<&Bound<'_, PyAny>>,
+) -> PyResult<PyRecordIterator> {
+    let source = Source::from_py(source)?;
+    let config = config_with_sheet(config_from_py(config)?, sheet);
+    Ok(spawn_records(move |on_record| match &source {
+        Source::Path(path) => crate::extract_records_with_config(path, &config, on_record),
+        Source::Bytes(data) => crate::extract_records_from_bytes(data, &config, on_record),
+    }))
+}
+
+/// 把交叉表逆透视为长格式记录，返回逐条产生 `{"sheet_name", "row", "record"}` 的迭代器
+#[pyfunction]
+#[pyo3(signature = (source, sheet = None, config = None))]
+fn unpivot_crosstabs(
+    source: &Bound<'_, PyAny>,
+    sheet: Option<String>,
+    config: Option<&Bound<'_, PyAny>>,
+) -> PyResult<PyRecordIterator> {
+    let source = Source::from_py(source)?;
+    let config = config_with_sheet(config_from_py(config)?, sheet);
+    Ok(spawn_records(move |on_record| match &source {
+        Source::Path(path) => crate::unpivot_crosstabs_with_config(path, &config, on_record),
+        Source::Bytes(data) => crate::unpivot_crosstabs_from_bytes(data, &config, on_record),
+    }))
+}
+
+#[pymodule]
+fn layout_view(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyClassifiedSheet>()?;
+    m.add_class::<PySheetDensity>()?;
+    m.add_class::<PyColumnDataTypeInfo>()?;
+    m.add_class::<PyRecordIterator>()?;
+    m.add("LayoutViewError", m.py().get_type::<LayoutViewError>())?;
+    m.add_function(wrap_pyfunction!(classify_excel_sheets, m)?)?;
+    m.add_function(wrap_pyfunction!(calculate_sheet_density, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_form_fields, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_records, m)?)?;
+    m.add_function(wrap_pyfunction!(unpivot_crosstabs, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! 工作表区域切分
+//!
+//! 使用递归 XY 切分（XY-cut）把有效范围拆成若干个由整行/整列空白分隔的数据块，
+//! 例如左右并排的两张表，或标题块下方的一张表。
+
+use crate::is_empty_cell;
+
+/// 区域边界 (first_row, first_col, end_row, end_col)，闭区间
+pub(crate) type RegionBounds = (u32, u32, u32, u32);
+
+/// 在给定范围内切分出所有数据块，按 (first_row, first_col) 排序
+pub(crate) fn detect_regions(
+    range: &calamine::Range<calamine::Data>,
+    start_row: u32,
+    start_col: u32,
+    end_row: u32,
+    end_col: u32,
+) -> Vec<RegionBounds> {
+    let (base_row, base_col) = range.start().unwrap_or((0, 0));
+    let cells: Vec<(u32, u32)> = range
+        .used_cells()
+        .filter(|(_, _, cell)| !is_empty_cell(cell))
+        .map(|(row, col, _)| (row as u32 + base_row, col as u32 + base_col))
+        .filter(|&(row, col)| {
+            (start_row..=end_row).contains(&row) && (start_col..=end_col).contains(&col)
+        })
+        .collect();
+
+    let mut regions = Vec::new();
+    if !cells.is_empty() {
+        xy_cut(cells, &mut regions);
+    }
+    regions.sort();
+    regions
+}
+
+/// 先按空行切分，无法切分时再按空列切分；两个方向都无法切分时即为一个数据块
+fn xy_cut(cells: Vec<(u32, u32)>, regions: &mut Vec<RegionBounds>) {
+    let row_groups = split_by_gap(cells, |&(row, _)| row);
+    if row_groups.len() > 1 {
+        for group in row_groups {
+            xy_cut(group, regions);
+        }
+        return;
+    }
+
+    let cells = row_groups.into_iter().next().unwrap_or_default();
+    let col_groups = split_by_gap(cells, |&(_, col)| col);
+    if col_groups.len() > 1 {
+        for group in col_groups {
+            xy_cut(group, regions);
+        }
+        return;
+    }
+
+    let cells = col_groups.into_iter().next().unwrap_or_default();
+    if let Some(bounds) = bounding_box(&cells) {
+        regions.push(bounds);
+    }
+}
+
+/// 按坐标排序后，在相邻坐标间隔超过 1（即中间存在整行/整列空白）处切开
+fn split_by_gap<F>(mut cells: Vec<(u32, u32)>, key: F) -> Vec<Vec<(u32, u32)>>
+where
+    F: Fn(&(u32, u32)) -> u32,
+{
+    cells.sort_by_key(|cell| key(cell));
+
+    let mut groups: Vec<Vec<(u32, u32)>> = Vec::new();
+    let mut last_key: Option<u32> = None;
+    for cell in cells {
+        let k = key(&cell);
+        match (last_key, groups.last_mut()) {
+            (Some(last), Some(group)) if k <= last + 1 => group.push(cell),
+            _ => groups.push(vec![cell]),
+        }
+        last_key = Some(k);
+    }
+    groups
+}
+
+fn bounding_box(cells: &[(u32, u32)]) -> Option<RegionBounds> {
+    let first = cells.first()?;
+    let mut bounds = (first.0, first.1, first.0, first.1);
+    for &(row, col) in cells {
+        bounds.0 = bounds.0.min(row);
+        bounds.1 = bounds.1.min(col);
+        bounds.2 = bounds.2.max(row);
+        bounds.3 = bounds.3.max(col);
+    }
+    Some(bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Data, Range};
+
+    fn fill(
+        range: &mut Range<Data>,
+        rows: std::ops::RangeInclusive<u32>,
+        cols: std::ops::RangeInclusive<u32>,
+    ) {
+        for row in rows {
+            for col in cols.clone() {
+                range.set_value((row, col), Data::Int(1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_regions() {
+        // 标题块 + 下方左右并排的两张表
+        let mut range = Range::new((0, 0), (9, 8));
+        range.set_value((0, 0), Data::String("Title".to_string()));
+        fill(&mut range, 2..=9, 0..=3);
+        fill(&mut range, 2..=6, 5..=8);
+        // 全空格字符串视为空白，不应连接两张表
+        range.set_value((3, 4), Data::String("  ".to_string()));
+
+        let regions = detect_regions(&range, 0, 0, 9, 8);
+        assert_eq!(regions, vec![(0, 0, 0, 0), (2, 0, 9, 3), (2, 5, 6, 8)]);
+    }
+
+    #[test]
+    fn test_detect_regions_single_block() {
+        let mut range = Range::new((1, 1), (4, 3));
+        fill(&mut range, 1..=4, 1..=3);
+
+        assert_eq!(detect_regions(&range, 1, 1, 4, 3), vec![(1, 1, 4, 3)]);
+        assert!(detect_regions(&Range::<Data>::empty(), 0, 0, 0, 0).is_empty());
+    }
+}
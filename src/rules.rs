@@ -0,0 +1,607 @@
+//! 分类规则
+//!
+//! 分类规则按优先级排列，第一条所有条件都满足的规则决定类型，都不满足时归为表单。
+//! 每条规则的条件都记录阈值和实际值，用于给出置信度、命中规则和接近命中的规则。
+//!
+//! 确定数据表/表单后，再用细分规则判断是否属于交叉表、清单、代码对照表、日历或说明文字，
+//! 细分结果单独记录在 `layout_type` 中，不改变 `sheet_type`。
+
+use crate::{ClassifierConfig, LayoutType, ShapeFeatures, SheetType};
+use serde::{Deserialize, Serialize};
+
+/// 参与分类的布局特征，工作表和数据块共用
+pub(crate) struct LayoutFeatures {
+    pub(crate) density: f64,
+    pub(crate) data_type_mix: f64,
+    pub(crate) row_type_consistency: f64,
+    pub(crate) aspect_ratio: f64,
+    pub(crate) col_count: u32,
+    pub(crate) merged_cell_ratio: f64,
+    pub(crate) cell_density: f64, // 只按非空单元格计算的密度，不受 density_mode 影响
+    pub(crate) row_count: u32,
+    pub(crate) shape: ShapeFeatures,
+    pub(crate) crosstab_detected: bool, // 是否识别出行标签列、列标签行和数值表体
+}
+
+/// 规则中的单个条件
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RuleCondition {
+    pub feature: String,  // 特征名称，如 density、col_count
+    pub operator: String, // 比较运算符：">"、"<"、"<="
+    pub threshold: f64,
+    pub value: f64,
+    pub passed: bool,
+    pub margin: f64, // 相对阈值的余量，正数表示满足条件，负数表示差多少才能满足
+}
+
+/// 未命中但接近命中的规则
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NearMissRule {
+    pub rule: String,
+    pub sheet_type: SheetType,
+    pub distance: f64, // 未满足条件中最大的相对差距
+    pub conditions: Vec<RuleCondition>,
+}
+
+/// 分类依据
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ClassificationExplanation {
+    pub matched_rule: String,
+    pub conditions: Vec<RuleCondition>, // 命中规则的条件
+    pub near_misses: Vec<NearMissRule>, // 给出其他类型、且只差一点就命中的规则
+    #[serde(default)]
+    pub layout_rule: String, // 命中的细分规则，没有命中时为空
+    #[serde(default)]
+    pub layout_conditions: Vec<RuleCondition>, // 命中细分规则的条件
+}
+
+/// 规则判断结果
+pub(crate) struct LayoutClassification {
+    pub(crate) sheet_type: SheetType,
+    pub(crate) layout_type: Option<LayoutType>,
+    pub(crate) reason: String,
+    pub(crate) confidence: f64,
+    pub(crate) explanation: ClassificationExplanation,
+}
+
+impl LayoutClassification {
+    /// 不经过规则判断、直接确定类型的情况（如密度为0）
+    pub(crate) fn fixed(sheet_type: SheetType, rule: &str, reason: &str) -> Self {
+        LayoutClassification {
+            sheet_type,
+            layout_type: None,
+            reason: reason.to_string(),
+            confidence: 1.0,
+            explanation: ClassificationExplanation {
+                matched_rule: rule.to_string(),
+                ..ClassificationExplanation::default()
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Operator {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// 分类规则，`T` 为命中时给出的类型（基本类型 `SheetType` 或细分类型 `LayoutType`）
+struct Rule<T> {
+    name: &'static str,
+    sheet_type: T,
+    conditions: Vec<RuleCondition>,
+}
+
+impl<T> Rule<T> {
+    fn new(name: &'static str, sheet_type: T, conditions: Vec<RuleCondition>) -> Self {
+        Rule {
+            name,
+            sheet_type,
+            conditions,
+        }
+    }
+
+    fn passed(&self) -> bool {
+        self.conditions.iter().all(|c| c.passed)
+    }
+
+    /// 距离命中还差多少：未满足条件中最大的相对差距
+    fn distance(&self) -> f64 {
+        self.conditions
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| -c.margin)
+            .fold(0.0, f64::max)
+    }
+
+    /// 命中的牢固程度：所有条件中最小的相对余量
+    fn strength(&self) -> Option<f64> {
+        self.conditions.iter().map(|c| c.margin).reduce(f64::min)
+    }
+}
+
+fn condition(feature: &str, value: f64, operator: Operator, threshold: f64) -> RuleCondition {
+    let passed = match operator {
+        Operator::Gt => value > threshold,
+        Operator::Ge => value >= threshold,
+        Operator::Lt => value < threshold,
+        Operator::Le => value <= threshold,
+    };
+    let diff = match operator {
+        Operator::Gt | Operator::Ge => value - threshold,
+        Operator::Lt | Operator::Le => threshold - value,
+    };
+    RuleCondition {
+        feature: feature.to_string(),
+        operator: match operator {
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+        }
+        .to_string(),
+        threshold,
+        value,
+        passed,
+        margin: diff / threshold.abs().max(f64::EPSILON),
+    }
+}
+
+/// 整数特征（列数）以两个整数的中点作为边界计算余量，
+/// 例如 `col_count <= 4` 在列数为 4 时余量为 0.5/4.5 而不是 0
+fn count_condition(feature: &str, value: u32, operator: Operator, threshold: u32) -> RuleCondition {
+    let boundary = match operator {
+        Operator::Gt | Operator::Le => threshold as f64 + 0.5,
+        Operator::Ge | Operator::Lt => threshold as f64 - 0.5,
+    };
+    let mut result = condition(feature, value as f64, operator, boundary);
+    result.threshold = threshold as f64;
+    result.passed = match operator {
+        Operator::Gt => value > threshold,
+        Operator::Ge => value >= threshold,
+        Operator::Lt => value < threshold,
+        Operator::Le => value <= threshold,
+    };
+    result
+}
+
+/// 按优先级排列的分类规则
+fn layout_rules(f: &LayoutFeatures, config: &ClassifierConfig) -> Vec<Rule<SheetType>> {
+    use Operator::*;
+
+    let density = |op, t| condition("density", f.density, op, t);
+    let mix = |op, t| condition("data_type_mix", f.data_type_mix, op, t);
+    let consistency = |op, t| condition("row_type_consistency", f.row_type_consistency, op, t);
+    let aspect = |op, t| condition("aspect_ratio", f.aspect_ratio, op, t);
+    let cols = |op, t| count_condition("col_count", f.col_count, op, t);
+    let merged = |op, t| condition("merged_cell_ratio", f.merged_cell_ratio, op, t);
+
+    vec![
+        // 大量单元格被合并且非空单元格不算密集 -> 表单（合并单元格常见于表单的标签和填写栏，
+        // 数据表通常只有标题行或个别分组列被合并，纵向合并分组列的数据表非空单元格仍然密集）
+        Rule::new(
+            "merged_form",
+            SheetType::Form,
+            vec![
+                merged(Gt, config.merged_form_ratio),
+                condition("cell_density", f.cell_density, Le, config.high_density),
+            ],
+        ),
+        // 极高密度几乎肯定是数据表
+        Rule::new(
+            "high_density",
+            SheetType::Data,
+            vec![density(Gt, config.high_density)],
+        ),
+        // 高瘦结构 + 少列 + 中等密度 -> 表单（垂直键值对表单）
+        Rule::new(
+            "tall_form",
+            SheetType::Form,
+            vec![
+                aspect(Gt, config.tall_form_aspect_ratio),
+                cols(Le, config.tall_form_max_cols),
+                density(Gt, config.tall_form_min_density),
+            ],
+        ),
+        // 高密度、多列、行一致性中等以上 -> 数据表
+        Rule::new(
+            "dense_multi_column",
+            SheetType::Data,
+            vec![
+                density(Gt, config.data_density),
+                cols(Gt, config.data_min_cols),
+                consistency(Gt, config.row_consistency),
+            ],
+        ),
+        // 行结构高度一致且密度中等以上 -> 数据表（需要更高的一致性阈值）
+        Rule::new(
+            "consistent_rows",
+            SheetType::Data,
+            vec![
+                density(Gt, config.consistent_density),
+                consistency(Gt, config.high_row_consistency),
+            ],
+        ),
+        // 宽表特征：列数多 + 宽高比低 + 密度中等以上 -> 数据表
+        Rule::new(
+            "wide_table",
+            SheetType::Data,
+            vec![
+                cols(Gt, config.wide_table_min_cols),
+                aspect(Lt, config.wide_table_aspect_ratio),
+                density(Gt, config.wide_table_min_density),
+            ],
+        ),
+        // 高密度且数据类型混合度高
+        Rule::new(
+            "dense_mixed_types",
+            SheetType::Data,
+            vec![
+                density(Gt, config.dense_mixed_density),
+                mix(Gt, config.dense_mixed_type_mix),
+            ],
+        ),
+        // 密度中等 + 数据类型混合度高 + 列数较多 -> 数据表
+        Rule::new(
+            "mixed_types_many_columns",
+            SheetType::Data,
+            vec![
+                density(Gt, config.mixed_density),
+                mix(Gt, config.mixed_type_mix),
+                cols(Gt, config.mixed_min_cols),
+            ],
+        ),
+    ]
+}
+
+/// 细分规则及其适用的基本类型
+struct Refinement {
+    rule: Rule<LayoutType>,
+    bases: &'static [SheetType],
+}
+
+/// 按优先级排列的细分规则
+fn refinement_rules(f: &LayoutFeatures, config: &ClassifierConfig) -> Vec<Refinement> {
+    use Operator::*;
+    use SheetType::{Data, Form};
+
+    let shape = &f.shape;
+    let cols = |op, t| count_condition("col_count", f.col_count, op, t);
+    let rows = |op, t| count_condition("row_count", f.row_count, op, t);
+
+    vec![
+        // 开头几行有星期名称，单元格多为日期数字 -> 日历
+        Refinement {
+            rule: Rule::new(
+                "calendar",
+                LayoutType::Calendar,
+                vec![
+                    count_condition(
+                        "weekday_cells",
+                        shape.weekday_cells,
+                        Ge,
+                        config.calendar_min_weekdays,
+                    ),
+                    condition(
+                        "calendar_ratio",
+                        shape.calendar_ratio,
+                        Gt,
+                        config.calendar_ratio,
+                    ),
+                ],
+            ),
+            bases: &[Data, Form],
+        },
+        // 几乎全是长文本 -> 说明文字
+        Refinement {
+            rule: Rule::new(
+                "notes",
+                LayoutType::Notes,
+                vec![
+                    condition(
+                        "avg_text_length",
+                        shape.avg_text_length,
+                        Gt,
+                        config.notes_min_text_length,
+                    ),
+                    condition("text_ratio", shape.text_ratio, Gt, config.notes_text_ratio),
+                ],
+            ),
+            bases: &[Data, Form],
+        },
+        // 只有一列 -> 清单
+        Refinement {
+            rule: Rule::new(
+                "single_column_list",
+                LayoutType::List,
+                vec![cols(Le, 1), rows(Ge, config.list_min_rows)],
+            ),
+            bases: &[Data, Form],
+        },
+        // 表头是期间、首列是行标签、表体是数值 -> 交叉表
+        // （排在代码对照表之前："地区 | 2023 | 2024" 这类三列交叉表的首列同样是唯一的短标签）
+        Refinement {
+            rule: Rule::new(
+                "crosstab",
+                LayoutType::Crosstab,
+                vec![
+                    count_condition("crosstab_structure", f.crosstab_detected as u32, Ge, 1),
+                    cols(Gt, 2),
+                    condition(
+                        "header_period_ratio",
+                        shape.header_period_ratio,
+                        Ge,
+                        config.crosstab_period_ratio,
+                    ),
+                    condition(
+                        "row_label_ratio",
+                        shape.row_label_ratio,
+                        Ge,
+                        config.crosstab_row_label_ratio,
+                    ),
+                    condition(
+                        "body_numeric_ratio",
+                        shape.body_numeric_ratio,
+                        Ge,
+                        config.crosstab_numeric_ratio,
+                    ),
+                ],
+            ),
+            bases: &[Data],
+        },
+        // 两三列、首列是互不相同的短编码 -> 代码对照表
+        Refinement {
+            rule: Rule::new(
+                "lookup_table",
+                LayoutType::Lookup,
+                vec![
+                    cols(Gt, 1),
+                    cols(Le, config.lookup_max_cols),
+                    rows(Ge, config.list_min_rows),
+                    condition(
+                        "key_unique_ratio",
+                        shape.key_unique_ratio,
+                        Ge,
+                        config.lookup_key_unique_ratio,
+                    ),
+                    condition(
+                        "key_avg_length",
+                        shape.key_avg_length,
+                        Le,
+                        config.lookup_key_max_length,
+                    ),
+                ],
+            ),
+            bases: &[Data, Form],
+        },
+    ]
+}
+
+/// 在基本类型确定后判断细分类型，返回细分类型、命中的规则名和条件
+fn refine_layout(
+    sheet_type: &SheetType,
+    features: &LayoutFeatures,
+    config: &ClassifierConfig,
+) -> Option<(LayoutType, &'static str, Vec<RuleCondition>)> {
+    refinement_rules(features, config)
+        .into_iter()
+        .find(|refinement| refinement.bases.contains(sheet_type) && refinement.rule.passed())
+        .map(|refinement| {
+            let rule = refinement.rule;
+            (rule.sheet_type, rule.name, rule.conditions)
+        })
+}
+
+/// 依次匹配分类规则，给出类型、置信度和分类依据
+///
+/// 置信度取决于结果离翻转有多远：命中规则中最接近阈值的条件余量，
+/// 以及排在它前面、会给出其他类型的规则还差多少命中，两者取小，
+/// 按 `confidence_margin` 线性映射到 [0, 1]：0 表示恰好落在规则边界上，
+/// 余量达到 `confidence_margin` 时为 1。
+pub(crate) fn classify_layout(
+    features: &LayoutFeatures,
+    config: &ClassifierConfig,
+) -> LayoutClassification {
+    // 忽略density=0的sheet
+    if features.density == 0.0 {
+        return LayoutClassification::fixed(SheetType::Unknown, "zero_density", "Density is zero");
+    }
+
+    let rules = layout_rules(features, config);
+    let matched = rules.iter().position(Rule::passed);
+    let (matched_rule, sheet_type, conditions) = match matched {
+        Some(index) => (
+            rules[index].name,
+            rules[index].sheet_type.clone(),
+            rules[index].conditions.clone(),
+        ),
+        // 其他情况视为表单
+        None => ("default_form", SheetType::Form, Vec::new()),
+    };
+    let preceding = &rules[..matched.unwrap_or(rules.len())];
+
+    let competitor_distance = preceding
+        .iter()
+        .filter(|rule| rule.sheet_type != sheet_type)
+        .map(Rule::distance)
+        .reduce(f64::min);
+    let strength = matched.and_then(|index| rules[index].strength());
+    let margin = match (strength, competitor_distance) {
+        (Some(a), Some(b)) => a.min(b),
+        (Some(a), None) | (None, Some(a)) => a,
+        (None, None) => config.confidence_margin,
+    };
+    // 代码中构造的配置可能没有经过 validate，余量不是正数时不做除法，避免 0/0 得到 NaN
+    let confidence = if config.confidence_margin > 0.0 {
+        (margin / config.confidence_margin).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+
+    let near_misses = rules
+        .iter()
+        .enumerate()
+        .filter(|(index, rule)| {
+            Some(*index) != matched
+                && rule.sheet_type != sheet_type
+                && !rule.passed()
+                && rule.distance() <= config.near_miss_margin
+        })
+        .map(|(_, rule)| NearMissRule {
+            rule: rule.name.to_string(),
+            sheet_type: rule.sheet_type.clone(),
+            distance: rule.distance(),
+            conditions: rule.conditions.clone(),
+        })
+        .collect();
+
+    let reason = format!(
+        "density: {:.3}, data_type_mix: {:.3}, row_consistency: {:.3}, aspect_ratio: {:.1}",
+        features.density,
+        features.data_type_mix,
+        features.row_type_consistency,
+        features.aspect_ratio
+    );
+
+    let (layout_type, layout_rule, layout_conditions) =
+        match refine_layout(&sheet_type, features, config) {
+            Some((layout_type, rule, conditions)) => {
+                (Some(layout_type), rule.to_string(), conditions)
+            }
+            None => (None, String::new(), Vec::new()),
+        };
+
+    LayoutClassification {
+        sheet_type,
+        layout_type,
+        reason,
+        confidence,
+        explanation: ClassificationExplanation {
+            matched_rule: matched_rule.to_string(),
+            conditions,
+            near_misses,
+            layout_rule,
+            layout_conditions,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(density: f64, aspect_ratio: f64, col_count: u32) -> LayoutFeatures {
+        LayoutFeatures {
+            density,
+            data_type_mix: 0.3,
+            row_type_consistency: 0.7,
+            aspect_ratio,
+            col_count,
+            merged_cell_ratio: 0.0,
+            cell_density: density,
+            row_count: 10,
+            shape: ShapeFeatures::default(),
+            crosstab_detected: false,
+        }
+    }
+
+    #[test]
+    fn test_classify_layout_explanation() {
+        let config = ClassifierConfig::default();
+
+        // test1_form.xlsx 的特征：命中 tall_form，同时离 high_density 还有一段距离
+        let result = classify_layout(&features(0.598, 5.75, 4), &config);
+        assert_eq!(result.sheet_type, SheetType::Form);
+        assert_eq!(result.explanation.matched_rule, "tall_form");
+        assert_eq!(result.explanation.conditions.len(), 3);
+        assert!(result.explanation.near_misses.is_empty());
+        assert!(result.confidence > 0.0 && result.confidence <= 1.0);
+
+        // 密度刚好超过阈值，置信度应较低，且 tall_form 之前没有其他规则
+        let borderline = classify_layout(&features(0.705, 1.0, 6), &config);
+        assert_eq!(borderline.explanation.matched_rule, "high_density");
+        assert!(borderline.confidence < 0.1);
+
+        // 所有规则都不满足时归为表单，接近命中的数据表规则应被列出
+        let fallback = classify_layout(&features(0.45, 1.0, 6), &config);
+        assert_eq!(fallback.explanation.matched_rule, "default_form");
+        assert!(fallback
+            .explanation
+            .near_misses
+            .iter()
+            .any(|rule| rule.rule == "dense_multi_column"));
+        assert!(fallback.confidence < 0.2);
+
+        // 代码中构造、没有经过 validate 的配置：余量为 0 时置信度为 1，而不是 NaN
+        let zero_margin = ClassifierConfig {
+            confidence_margin: 0.0,
+            ..ClassifierConfig::default()
+        };
+        let result = classify_layout(&features(0.705, 1.0, 6), &zero_margin);
+        assert_eq!(result.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_refine_layout() {
+        let config = ClassifierConfig::default();
+
+        // 行标签 × 季度的数值表：基本类型仍为 Data，细分为交叉表
+        let mut crosstab = features(0.9, 1.0, 5);
+        crosstab.crosstab_detected = true;
+        crosstab.shape = ShapeFeatures {
+            header_period_ratio: 1.0,
+            row_label_ratio: 1.0,
+            body_numeric_ratio: 1.0,
+            ..ShapeFeatures::default()
+        };
+        let result = classify_layout(&crosstab, &config);
+        assert_eq!(result.sheet_type, SheetType::Data);
+        assert_eq!(result.layout_type, Some(LayoutType::Crosstab));
+        assert_eq!(result.explanation.layout_rule, "crosstab");
+        assert_eq!(result.explanation.layout_conditions.len(), 5);
+
+        // 单列清单
+        let list = classify_layout(&features(0.9, 10.0, 1), &config);
+        assert_eq!(list.layout_type, Some(LayoutType::List));
+
+        // 没有命中细分规则时没有细分类型
+        let plain = classify_layout(&features(0.9, 1.0, 6), &config);
+        assert_eq!(plain.layout_type, None);
+        assert!(plain.explanation.layout_rule.is_empty());
+    }
+
+    #[test]
+    fn test_merged_form_requires_sparse_cells() {
+        let config = ClassifierConfig::default();
+
+        // 纵向合并了分组列的密集数据表仍是数据表
+        let mut grouped = features(0.85, 2.0, 3);
+        grouped.merged_cell_ratio = 0.33;
+        let result = classify_layout(&grouped, &config);
+        assert_eq!(result.sheet_type, SheetType::Data);
+        assert_eq!(result.explanation.matched_rule, "high_density");
+
+        // 合并单元格计为有数据时密度很高，但非空单元格稀疏的表单仍判为表单
+        let mut form = features(1.0, 2.0, 3);
+        form.merged_cell_ratio = 0.8;
+        form.cell_density = 0.3;
+        let result = classify_layout(&form, &config);
+        assert_eq!(result.sheet_type, SheetType::Form);
+        assert_eq!(result.explanation.matched_rule, "merged_form");
+    }
+
+    #[test]
+    fn test_count_condition_margin() {
+        let passed = count_condition("col_count", 4, Operator::Le, 4);
+        assert!(passed.passed);
+        assert!(passed.margin > 0.0);
+        assert_eq!(passed.threshold, 4.0);
+
+        let failed = count_condition("col_count", 5, Operator::Le, 4);
+        assert!(!failed.passed);
+        assert!(failed.margin < 0.0);
+    }
+}
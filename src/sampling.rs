@@ -0,0 +1,103 @@
+//! 采样行选择
+//!
+//! 大表只取部分行计算密度、类型分布等指标。默认取前 100 行，
+//! 对前面有较长说明文字、或表单表头下方接长表格的工作表，可以改为全表扫描、
+//! 首尾各取一部分，或在整个范围内等间隔取行。
+
+use serde::{Deserialize, Serialize};
+
+/// 采样策略
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingStrategy {
+    #[default]
+    Head, // 取前 sample_rows 行
+    HeadTail, // 前后各取一半
+    Strided,  // 在整个范围内等间隔取 sample_rows 行（包含首行和末行）
+    Full,     // 全表扫描，忽略 sample_rows
+}
+
+/// 采样配置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SamplingConfig {
+    pub strategy: SamplingStrategy,
+    pub sample_rows: u32, // 最多采样的行数
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        SamplingConfig {
+            strategy: SamplingStrategy::Head,
+            sample_rows: 100,
+        }
+    }
+}
+
+/// 按采样策略选出 start_row..=end_row 中参与计算的行，结果按行号升序排列
+pub(crate) fn sample_rows(start_row: u32, end_row: u32, config: &SamplingConfig) -> Vec<u32> {
+    if start_row > end_row {
+        return Vec::new();
+    }
+
+    let total = end_row - start_row + 1;
+    let limit = config.sample_rows.max(1);
+    match config.strategy {
+        // 全表扫描，或总行数不超过采样行数时取全部行
+        SamplingStrategy::Full => (start_row..=end_row).collect(),
+        _ if total <= limit => (start_row..=end_row).collect(),
+        SamplingStrategy::Head => (start_row..start_row + limit).collect(),
+        SamplingStrategy::HeadTail => {
+            let head = limit.div_ceil(2);
+            let tail = limit - head;
+            (start_row..start_row + head)
+                .chain(end_row + 1 - tail..=end_row)
+                .collect()
+        }
+        SamplingStrategy::Strided if limit == 1 => vec![start_row],
+        SamplingStrategy::Strided => {
+            let step = (total - 1) as f64 / (limit - 1) as f64;
+            (0..limit)
+                .map(|i| start_row + (i as f64 * step).round() as u32)
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(strategy: SamplingStrategy, sample_rows: u32) -> SamplingConfig {
+        SamplingConfig {
+            strategy,
+            sample_rows,
+        }
+    }
+
+    #[test]
+    fn test_sample_rows() {
+        assert_eq!(
+            sample_rows(10, 1000, &config(SamplingStrategy::Head, 3)),
+            vec![10, 11, 12]
+        );
+        assert_eq!(
+            sample_rows(0, 99, &config(SamplingStrategy::HeadTail, 5)),
+            vec![0, 1, 2, 98, 99]
+        );
+        assert_eq!(
+            sample_rows(0, 100, &config(SamplingStrategy::Strided, 5)),
+            vec![0, 25, 50, 75, 100]
+        );
+        assert_eq!(
+            sample_rows(5, 9, &config(SamplingStrategy::Full, 2)).len(),
+            5
+        );
+        // 总行数不超过采样行数时取全部行
+        assert_eq!(
+            sample_rows(0, 2, &config(SamplingStrategy::Strided, 10)),
+            vec![0, 1, 2]
+        );
+        assert!(sample_rows(3, 2, &SamplingConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+//! 列语义类型识别
+//!
+//! 在数值/文本之上判断每列的业务含义：编号、身份证号、手机号、邮箱、网址、邮编、
+//! 金额、百分比、日期时间、分类（取值很少）或自由文本。
+//! 每种类型统计列中匹配的单元格占比，按优先级取第一个占比达到阈值的类型。
+
+use crate::datatype::{cell_category, CellCategory};
+use crate::numeric::{is_numeric_cell, parse_numeric_string, NumberLocale};
+use crate::{format_cell, is_empty_cell};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+// 匹配占比达到该值时认为整列属于该类型
+const MIN_MATCH_RATIO: f64 = 0.8;
+// 分类列最多的不同取值数
+const MAX_CATEGORY_VALUES: usize = 20;
+// 编号的最少位数（更短的编号需要是连续整数，以免把金额、数量当成编号）
+const MIN_ID_DIGITS: usize = 8;
+
+/// 列语义类型
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SemanticType {
+    IntegerId,      // 整数编号
+    ResidentId,     // 中国居民身份证号
+    MobilePhone,    // 手机号
+    Email,          // 电子邮箱
+    Url,            // 网址
+    PostalCode,     // 邮政编码
+    CurrencyAmount, // 带货币符号的金额
+    Percentage,     // 百分比
+    DateTime,       // 日期、时间或时长
+    Number,         // 其他数值
+    Category,       // 取值很少的文本
+    FreeText,       // 自由文本
+    #[default]
+    Unknown, // 空列
+}
+
+/// 列语义识别结果
+pub(crate) struct ColumnSemantics {
+    pub(crate) semantic_type: SemanticType,
+    pub(crate) ratio: f64,
+    pub(crate) matches: BTreeMap<SemanticType, f64>,
+}
+
+lazy_static! {
+    static ref MOBILE_RE: Regex = Regex::new(r"^(?:\+?86)?1[3-9]\d{9}$").unwrap();
+    static ref EMAIL_RE: Regex =
+        Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}$").unwrap();
+    static ref URL_RE: Regex =
+        Regex::new(r"^(?i:(?:https?|ftp)://\S+|www\.[^\s.]+\.\S+)$").unwrap();
+    static ref POSTAL_CODE_RE: Regex = Regex::new(r"^\d{6}$").unwrap();
+    static ref RESIDENT_ID_RE: Regex = Regex::new(r"^\d{17}[\dXx]$").unwrap();
+}
+
+/// 按优先级排列、可以直接由单元格匹配的类型（编号单独判断）
+const PATTERN_TYPES: [SemanticType; 9] = [
+    SemanticType::ResidentId,
+    SemanticType::MobilePhone,
+    SemanticType::Email,
+    SemanticType::Url,
+    SemanticType::PostalCode,
+    SemanticType::CurrencyAmount,
+    SemanticType::Percentage,
+    SemanticType::DateTime,
+    SemanticType::Number,
+];
+
+/// 识别一列在给定行中的语义类型，错误值不参与统计
+pub(crate) fn detect_column_semantics(
+    range: &calamine::Range<calamine::Data>,
+    rows: &[u32],
+    col: u32,
+    locale: NumberLocale,
+) -> ColumnSemantics {
+    let mut counts: BTreeMap<SemanticType, u32> = BTreeMap::new();
+    let mut total = 0;
+    let mut text_count = 0;
+    let mut distinct: HashSet<String> = HashSet::new();
+    let mut integers: Vec<(i128, usize)> = Vec::new(); // 编号候选的值和位数
+
+    for &row in rows {
+        let Some(cell) = range.get_value((row, col)) else {
+            continue;
+        };
+        let Some(category) = cell_category(cell, locale) else {
+            continue;
+        };
+        if category == CellCategory::Error || is_empty_cell(cell) {
+            continue;
+        }
+
+        total += 1;
+        let text = format_cell(cell);
+        distinct.insert(text.clone());
+        for semantic_type in cell_matches(cell, &text, category, locale) {
+            *counts.entry(semantic_type).or_default() += 1;
+        }
+        if matches!(category, CellCategory::Text | CellCategory::Bool) {
+            text_count += 1;
+        }
+        if let Some(value) = integer_value(cell, &text) {
+            integers.push((value, text.len()));
+        }
+    }
+
+    if total == 0 {
+        return ColumnSemantics {
+            semantic_type: SemanticType::Unknown,
+            ratio: 0.0,
+            matches: BTreeMap::new(),
+        };
+    }
+
+    let ratio = |count: u32| count as f64 / total as f64;
+    let mut matches: BTreeMap<SemanticType, f64> = counts
+        .iter()
+        .map(|(&semantic_type, &count)| (semantic_type, ratio(count)))
+        .collect();
+    let id_ratio = ratio(integers.len() as u32);
+    if id_ratio > 0.0 {
+        matches.insert(SemanticType::IntegerId, id_ratio);
+    }
+
+    let pattern = |semantic_type: SemanticType| {
+        let ratio = matches.get(&semantic_type).copied().unwrap_or(0.0);
+        (ratio >= MIN_MATCH_RATIO).then_some((semantic_type, ratio))
+    };
+    let integer_id = || {
+        let is_id = id_ratio >= MIN_MATCH_RATIO && is_id_sequence(&integers);
+        is_id.then_some((SemanticType::IntegerId, id_ratio))
+    };
+    let text = || {
+        let text_ratio = ratio(text_count);
+        if text_ratio < MIN_MATCH_RATIO {
+            return None;
+        }
+        let category = distinct.len() <= MAX_CATEGORY_VALUES && distinct.len() * 2 <= total;
+        Some(if category && total >= 4 {
+            (SemanticType::Category, text_ratio)
+        } else {
+            (SemanticType::FreeText, text_ratio)
+        })
+    };
+
+    // 编号排在具体格式之后、普通数值之前
+    let (semantic_type, best_ratio) = PATTERN_TYPES[..PATTERN_TYPES.len() - 1]
+        .iter()
+        .find_map(|&semantic_type| pattern(semantic_type))
+        .or_else(integer_id)
+        .or_else(|| pattern(SemanticType::Number))
+        .or_else(text)
+        .unwrap_or((SemanticType::FreeText, ratio(text_count)));
+
+    ColumnSemantics {
+        semantic_type,
+        ratio: best_ratio,
+        matches,
+    }
+}
+
+/// 单元格匹配的格式类型（不含编号、分类和自由文本）
+fn cell_matches(
+    cell: &calamine::Data,
+    text: &str,
+    category: CellCategory,
+    locale: NumberLocale,
+) -> Vec<SemanticType> {
+    let mut result = Vec::new();
+    let is_string = matches!(cell, calamine::Data::String(_));
+    let compact: String = text.chars().filter(|c| !matches!(c, ' ' | '-')).collect();
+
+    if is_resident_id(text) {
+        result.push(SemanticType::ResidentId);
+    }
+    if MOBILE_RE.is_match(&compact) {
+        result.push(SemanticType::MobilePhone);
+    }
+    if EMAIL_RE.is_match(text) {
+        result.push(SemanticType::Email);
+    }
+    if URL_RE.is_match(text) {
+        result.push(SemanticType::Url);
+    }
+    if is_string && POSTAL_CODE_RE.is_match(text) {
+        result.push(SemanticType::PostalCode);
+    }
+    if is_string {
+        if let Some(form) = parse_numeric_string(text, locale) {
+            if form.currency {
+                result.push(SemanticType::CurrencyAmount);
+            }
+            if form.percent {
+                result.push(SemanticType::Percentage);
+            }
+        }
+    }
+    if matches!(
+        category,
+        CellCategory::Date | CellCategory::DateTime | CellCategory::Time | CellCategory::Duration
+    ) {
+        result.push(SemanticType::DateTime);
+    }
+    if is_numeric_cell(cell, locale) {
+        result.push(SemanticType::Number);
+    }
+    result
+}
+
+/// 18 位居民身份证号：前 17 位数字加校验码，出生日期的月、日合法
+fn is_resident_id(s: &str) -> bool {
+    if !RESIDENT_ID_RE.is_match(s) {
+        return false;
+    }
+    const WEIGHTS: [u32; 17] = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+    const CHECK_CODES: &[u8; 11] = b"10X98765432";
+
+    let bytes = s.as_bytes();
+    let sum: u32 = WEIGHTS
+        .iter()
+        .zip(bytes)
+        .map(|(weight, digit)| weight * (digit - b'0') as u32)
+        .sum();
+    let month: u32 = s[10..12].parse().unwrap_or(0);
+    let day: u32 = s[12..14].parse().unwrap_or(0);
+    bytes[17].to_ascii_uppercase() == CHECK_CODES[(sum % 11) as usize]
+        && (1..=12).contains(&month)
+        && (1..=31).contains(&day)
+}
+
+/// 非负整数或纯数字字符串（最多 38 位）的值
+fn integer_value(cell: &calamine::Data, text: &str) -> Option<i128> {
+    match cell {
+        calamine::Data::Int(i) if *i >= 0 => Some(*i as i128),
+        calamine::Data::Float(f) if *f >= 0.0 && f.fract() == 0.0 && *f < 9.0e15 => {
+            Some(*f as i128)
+        }
+        calamine::Data::String(_)
+            if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            text.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// 编号列：取值互不相同（"007" 和 "7" 按位数区分），且位数较多或是连续整数
+fn is_id_sequence(integers: &[(i128, usize)]) -> bool {
+    if integers.len() < 3 {
+        return false;
+    }
+    let values: HashSet<(i128, usize)> = integers.iter().copied().collect();
+    if values.len() != integers.len() {
+        return false;
+    }
+    if integers.iter().all(|(_, digits)| *digits >= MIN_ID_DIGITS) {
+        return true;
+    }
+    let min = integers.iter().map(|(value, _)| *value).min().unwrap_or(0);
+    let max = integers.iter().map(|(value, _)| *value).max().unwrap_or(0);
+    // 取值都不为负，差值不会溢出 i128；在 i128 中比较，避免转换为 usize 时截断或回绕
+    max - min + 1 == integers.len() as i128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Data, Range};
+
+    fn column(values: &[Data]) -> ColumnSemantics {
+        let mut range = Range::new((0, 0), (values.len() as u32 - 1, 0));
+        for (row, value) in values.iter().enumerate() {
+            range.set_value((row as u32, 0), value.clone());
+        }
+        let rows: Vec<u32> = (0..values.len() as u32).collect();
+        detect_column_semantics(&range, &rows, 0, NumberLocale::Auto)
+    }
+
+    fn text(values: &[&str]) -> Vec<Data> {
+        values.iter().map(|s| Data::String(s.to_string())).collect()
+    }
+
+    #[test]
+    fn test_id_sequence_with_huge_values() {
+        // 大数与小数混合时不能因为差值转换为 usize 而溢出或截断
+        assert!(!is_id_sequence(&[
+            (1, 1),
+            (2, 1),
+            (18446744073709551616, 20)
+        ]));
+        assert!(!is_id_sequence(&[(0, 1), (1, 1), (4294967298, 10)]));
+        let huge = 10i128.pow(37);
+        assert!(!is_id_sequence(&[(0, 1), (huge, 38), (huge * 9, 38)]));
+        assert!(is_id_sequence(&[(7, 1), (8, 1), (9, 1)]));
+
+        let mixed = column(&text(&["1", "2", "18446744073709551616"]));
+        assert_ne!(mixed.semantic_type, SemanticType::IntegerId);
+        let mixed = column(&text(&["0", "1", "4294967298"]));
+        assert_ne!(mixed.semantic_type, SemanticType::IntegerId);
+    }
+
+    #[test]
+    fn test_resident_id() {
+        assert!(is_resident_id("11010519491231002X"));
+        assert!(!is_resident_id("110105194912310021"));
+        assert!(!is_resident_id("3184392********444"));
+    }
+
+    #[test]
+    fn test_detect_column_semantics() {
+        let phones = column(&text(&["13800000000", "139-1234-5678", "15012345678"]));
+        assert_eq!(phones.semantic_type, SemanticType::MobilePhone);
+        assert_eq!(phones.ratio, 1.0);
+
+        let emails = column(&text(&["a@example.com", "b.c@example.cn", "n/a"]));
+        assert_eq!(emails.semantic_type, SemanticType::FreeText);
+        assert!((emails.matches[&SemanticType::Email] - 2.0 / 3.0).abs() < 1e-9);
+
+        let amounts = column(&text(&["¥1,234.00", "¥56", "¥7.5"]));
+        assert_eq!(amounts.semantic_type, SemanticType::CurrencyAmount);
+
+        // 连续整数是编号，不连续的小整数是普通数值
+        let ids: Vec<Data> = (1..=5).map(Data::Int).collect();
+        assert_eq!(column(&ids).semantic_type, SemanticType::IntegerId);
+        let quantities = vec![Data::Int(3), Data::Int(7), Data::Int(2), Data::Int(7)];
+        assert_eq!(column(&quantities).semantic_type, SemanticType::Number);
+        // 以字符串保存的小整数同样要求互不相同且连续
+        let text_quantities = column(&text(&["3", "7", "2", "7"]));
+        assert_eq!(text_quantities.semantic_type, SemanticType::Number);
+        let text_ids = column(&text(&["3", "1", "2", "4"]));
+        assert_eq!(text_ids.semantic_type, SemanticType::IntegerId);
+
+        let codes = column(&text(&["000123", "000456", "000789"]));
+        assert_eq!(codes.semantic_type, SemanticType::PostalCode);
+        let long_ids = column(&text(&[
+            "20240001000001",
+            "20240001000002",
+            "20240001000003",
+        ]));
+        assert_eq!(long_ids.semantic_type, SemanticType::IntegerId);
+
+        let status = column(&text(&["在职", "离职", "在职", "在职", "离职", "在职"]));
+        assert_eq!(status.semantic_type, SemanticType::Category);
+        let names = column(&text(&["张三", "李四", "王五", "赵六"]));
+        assert_eq!(names.semantic_type, SemanticType::FreeText);
+
+        assert_eq!(column(&[Data::Empty]).semantic_type, SemanticType::Unknown);
+    }
+}
@@ -0,0 +1,332 @@
+//! 细分布局特征
+//!
+//! 在数据表/表单之外区分交叉表、单列清单、代码对照表、日历和说明文字，
+//! 这里计算区分它们所需的特征：
+//! - 交叉表：左侧一列行标签，表头是年份、季度、月份等期间，表体几乎全是数值
+//! - 代码对照表：两三列，首列是互不相同的短编码
+//! - 日历：表头有星期名称，单元格多为 1-31 的日期数字
+//! - 说明文字：几乎全是长文本
+
+use crate::datatype::{cell_category, CellCategory};
+use crate::numeric::NumberLocale;
+use crate::{format_cell, is_empty_cell};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// 在数据块开头的若干行中寻找星期名称
+const WEEKDAY_SCAN_ROWS: u32 = 5;
+
+lazy_static! {
+    // 年份、季度、月份、财年及其组合（"2024"、"2024年"、"Q1"、"2024Q1"、"2024/Q1"、"一季度"、"3月"、"2024-03"、"FY24"）
+    static ref PERIOD_RE: Regex = Regex::new(
+        r"(?ix)^(?:
+            (?:FY)?(?:19|20)\d{2}\s*年?(?:\s*[-/.\s]?\s*(?:Q[1-4]|H[12]|(?:0?[1-9]|1[0-2])\s*月?|[上下]半年|第?[一二三四1-4]季度?))?
+            | FY\d{2}
+            | Q[1-4] | H[12]
+            | 第?[一二三四1-4]季度
+            | [上下]半年
+            | (?:0?[1-9]|1[0-2])月(?:份)?
+            | (?:jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sept?(?:ember)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)\.?(?:\s*[-']?\s*\d{2,4})?
+        )$"
+    )
+    .unwrap();
+    // 交叉表中的汇总行列
+    static ref TOTAL_RE: Regex = Regex::new(r"(?i)^(?:合计|总计|小计|汇总|grand\s*total|sub\s*total|total)$").unwrap();
+    // 星期名称
+    static ref WEEKDAY_RE: Regex = Regex::new(
+        r"(?i)^(?:(?:mon|tue|tues|wed|thu|thur|thurs|fri|sat|sun)(?:day|nesday|urday|sday)?\.?|(?:星期|周|礼拜)[一二三四五六日天])$"
+    )
+    .unwrap();
+    // 单字星期名称，同时也是常见的中文序号，只在一行中连续 7 列凑齐时才算星期
+    static ref SHORT_WEEKDAY_RE: Regex = Regex::new(r"^[一二三四五六日]$").unwrap();
+    // 期间标签中表示季度、半年、月份的部分，用于区分期间的粒度
+    static ref QUARTER_RE: Regex = Regex::new(r"(?i)Q[1-4]|季").unwrap();
+    static ref HALF_YEAR_RE: Regex = Regex::new(r"(?i)H[12]|半年").unwrap();
+    static ref MONTH_RE: Regex = Regex::new(r"月|[A-Za-z]{3}|\d{4}\s*[-/.\s]\s*\d").unwrap();
+}
+
+/// 区分细分布局类型的特征
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ShapeFeatures {
+    pub text_ratio: f64,          // 文本单元格占非空单元格的比例
+    pub avg_text_length: f64,     // 文本单元格的平均字符数
+    pub key_unique_ratio: f64,    // 首列（表头之下）不同取值的比例
+    pub key_avg_length: f64,      // 首列（表头之下）取值的平均字符数
+    pub header_period_ratio: f64, // 表头行除首列外是期间（年、季、月等）或合计的比例
+    pub body_numeric_ratio: f64,  // 表头之下除首列外数值单元格的比例
+    pub row_label_ratio: f64,     // 表头之下首列为文本标签的比例
+    pub calendar_ratio: f64,      // 非空单元格中 1-31 的日期数字、星期名称和日期的比例
+    pub weekday_cells: u32,       // 开头若干行中不同星期名称的个数
+}
+
+/// 计算数据块的细分布局特征
+/// `header_row` 为识别出的表头行，没有表头时以数据块首行作为表头候选
+pub(crate) fn shape_features(
+    range: &calamine::Range<calamine::Data>,
+    rows: &[u32],
+    bounds: (u32, u32, u32, u32),
+    header_row: Option<u32>,
+    locale: NumberLocale,
+) -> ShapeFeatures {
+    let (start_row, start_col, end_row, end_col) = bounds;
+    let header_row = header_row.unwrap_or(start_row);
+    let body_rows: Vec<u32> = rows.iter().copied().filter(|&r| r > header_row).collect();
+    let cell = |row: u32, col: u32| range.get_value((row, col)).filter(|c| !is_empty_cell(c));
+    let weekday_rows = start_row..=end_row.min(start_row + WEEKDAY_SCAN_ROWS - 1);
+    let short_weekdays = short_weekday_cells(&cell, weekday_rows.clone(), start_col, end_col);
+
+    // 文本占比、平均长度和日历单元格
+    let mut non_empty = 0;
+    let mut text_cells = 0;
+    let mut text_chars = 0;
+    let mut calendar_cells = 0;
+    for &row in rows {
+        for col in start_col..=end_col {
+            let Some(value) = cell(row, col) else {
+                continue;
+            };
+            non_empty += 1;
+            let category = cell_category(value, locale);
+            if category == Some(CellCategory::Text) {
+                text_cells += 1;
+                text_chars += format_cell(value).chars().count();
+            }
+            if is_calendar_cell(value, category) || short_weekdays.contains(&(row, col)) {
+                calendar_cells += 1;
+            }
+        }
+    }
+
+    // 首列：编码是否唯一、是否为文本标签
+    let keys: Vec<String> = body_rows
+        .iter()
+        .filter_map(|&row| cell(row, start_col))
+        .map(format_cell)
+        .collect();
+    let distinct_keys: HashSet<&String> = keys.iter().collect();
+    let row_labels = body_rows
+        .iter()
+        .filter_map(|&row| cell(row, start_col))
+        .filter(|value| cell_category(value, locale) == Some(CellCategory::Text))
+        .count();
+
+    // 表头行除首列外的期间标签
+    let header_cells: Vec<String> = (start_col + 1..=end_col)
+        .filter_map(|col| cell(header_row, col))
+        .map(format_cell)
+        .collect();
+    let period_cells = header_cells
+        .iter()
+        .filter(|text| is_period_label(text))
+        .count();
+
+    // 表体除首列外的数值单元格
+    let mut body_cells = 0;
+    let mut body_numeric = 0;
+    for &row in &body_rows {
+        for col in start_col + 1..=end_col {
+            if let Some(value) = cell(row, col) {
+                body_cells += 1;
+                if cell_category(value, locale) == Some(CellCategory::Numeric) {
+                    body_numeric += 1;
+                }
+            }
+        }
+    }
+
+    // 开头若干行中出现的不同星期名称
+    let weekdays: HashSet<String> = weekday_rows
+        .flat_map(|row| (start_col..=end_col).map(move |col| (row, col)))
+        .filter_map(|(row, col)| {
+            let text = format_cell(cell(row, col)?);
+            let weekday = WEEKDAY_RE.is_match(&text) || short_weekdays.contains(&(row, col));
+            weekday.then(|| text.to_lowercase())
+        })
+        .collect();
+
+    let ratio = |count: usize, total: usize| {
+        if total > 0 {
+            count as f64 / total as f64
+        } else {
+            0.0
+        }
+    };
+
+    ShapeFeatures {
+        text_ratio: ratio(text_cells, non_empty),
+        avg_text_length: ratio(text_chars, text_cells),
+        key_unique_ratio: ratio(distinct_keys.len(), keys.len()),
+        key_avg_length: ratio(keys.iter().map(|k| k.chars().count()).sum(), keys.len()),
+        header_period_ratio: ratio(period_cells, header_cells.len()),
+        body_numeric_ratio: ratio(body_numeric, body_cells),
+        row_label_ratio: ratio(row_labels, body_rows.len()),
+        calendar_ratio: ratio(calendar_cells, non_empty),
+        weekday_cells: weekdays.len() as u32,
+    }
+}
+
+/// 期间标签（年、季度、月份等）或汇总列
+pub(crate) fn is_period_label(text: &str) -> bool {
+    let text = text.trim();
+    PERIOD_RE.is_match(text) || is_total_label(text)
+}
+
+/// 期间标签的粒度："year"、"half_year"、"quarter" 或 "month"，不是期间标签时返回 None
+/// 带年份的组合标签（"2024Q1"、"2024年3月"）取较细的粒度
+pub(crate) fn period_kind(text: &str) -> Option<&'static str> {
+    let text = text.trim();
+    if !PERIOD_RE.is_match(text) {
+        return None;
+    }
+    Some(if QUARTER_RE.is_match(text) {
+        "quarter"
+    } else if HALF_YEAR_RE.is_match(text) {
+        "half_year"
+    } else if MONTH_RE.is_match(text) {
+        "month"
+    } else {
+        "year"
+    })
+}
+
+/// 汇总行列的标签（合计、小计、Total 等）
+pub(crate) fn is_total_label(text: &str) -> bool {
+    TOTAL_RE.is_match(text.trim())
+}
+
+/// 一行中连续 7 列恰好是七个不同单字星期名称（"日一二三四五六"）的单元格
+fn short_weekday_cells<'a>(
+    cell: &impl Fn(u32, u32) -> Option<&'a calamine::Data>,
+    rows: impl Iterator<Item = u32>,
+    start_col: u32,
+    end_col: u32,
+) -> HashSet<(u32, u32)> {
+    let mut result = HashSet::new();
+    for row in rows {
+        let mut col = start_col;
+        while col + 6 <= end_col {
+            let names: HashSet<String> = (col..col + 7)
+                .filter_map(|c| cell(row, c))
+                .map(|value| format_cell(value).trim().to_string())
+                .filter(|text| SHORT_WEEKDAY_RE.is_match(text))
+                .collect();
+            if names.len() == 7 {
+                result.extend((col..col + 7).map(|c| (row, c)));
+                col += 7;
+            } else {
+                col += 1;
+            }
+        }
+    }
+    result
+}
+
+/// 日历中的单元格：1-31 的整数、星期名称或日期
+fn is_calendar_cell(cell: &calamine::Data, category: Option<CellCategory>) -> bool {
+    let day = match cell {
+        calamine::Data::Int(i) => Some(*i as f64),
+        calamine::Data::Float(f) => Some(*f),
+        calamine::Data::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    if day.is_some_and(|d| d.fract() == 0.0 && (1.0..=31.0).contains(&d)) {
+        return true;
+    }
+    category == Some(CellCategory::Date) || WEEKDAY_RE.is_match(format_cell(cell).as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Data, Range};
+
+    #[test]
+    fn test_period_label() {
+        for label in [
+            "2024",
+            "2024年",
+            "Q1",
+            "2024Q1",
+            "2024/Q1",
+            "2024-03",
+            "一季度",
+            "3月",
+            "Mar",
+            "Jan-24",
+            "合计",
+            "Total",
+        ] {
+            assert!(is_period_label(label), "{}", label);
+        }
+        for label in ["姓名", "Amount", "Market", "2024-01-05x", "13月"] {
+            assert!(!is_period_label(label), "{}", label);
+        }
+        for (label, kind) in [
+            ("2024", "year"),
+            ("FY24", "year"),
+            ("2024Q1", "quarter"),
+            ("一季度", "quarter"),
+            ("上半年", "half_year"),
+            ("2024-03", "month"),
+            ("3月", "month"),
+            ("Jan-24", "month"),
+        ] {
+            assert_eq!(period_kind(label), Some(kind), "{}", label);
+        }
+        assert_eq!(period_kind("合计"), None);
+    }
+
+    #[test]
+    fn test_shape_features_crosstab_and_calendar() {
+        // 交叉表：首行是季度，首列是地区，表体是数值
+        let mut range = Range::new((0, 0), (3, 4));
+        for (col, label) in ["地区", "Q1", "Q2", "Q3", "合计"].iter().enumerate() {
+            range.set_value((0, col as u32), Data::String(label.to_string()));
+        }
+        for (row, region) in ["华东", "华南", "华北"].iter().enumerate() {
+            let row = row as u32 + 1;
+            range.set_value((row, 0), Data::String(region.to_string()));
+            for col in 1..=4 {
+                range.set_value((row, col), Data::Float(row as f64 * 10.5));
+            }
+        }
+        let rows: Vec<u32> = (0..=3).collect();
+        let features = shape_features(&range, &rows, (0, 0, 3, 4), Some(0), NumberLocale::Auto);
+        assert_eq!(features.header_period_ratio, 1.0);
+        assert_eq!(features.body_numeric_ratio, 1.0);
+        assert_eq!(features.row_label_ratio, 1.0);
+        assert_eq!(features.key_unique_ratio, 1.0);
+
+        // 日历：星期表头加日期数字
+        let mut range = Range::new((0, 0), (2, 6));
+        for (col, label) in ["日", "一", "二", "三", "四", "五", "六"]
+            .iter()
+            .enumerate()
+        {
+            range.set_value((0, col as u32), Data::String(label.to_string()));
+            range.set_value((1, col as u32), Data::Int(col as i64 + 1));
+            range.set_value((2, col as u32), Data::Int(col as i64 + 8));
+        }
+        let rows: Vec<u32> = (0..=2).collect();
+        let features = shape_features(&range, &rows, (0, 0, 2, 6), None, NumberLocale::Auto);
+        assert_eq!(features.weekday_cells, 7);
+        assert_eq!(features.calendar_ratio, 1.0);
+
+        // 中文序号（"一"、"二"……）竖排在首列时不是星期名称
+        let mut range = Range::new((0, 0), (6, 1));
+        for (row, label) in ["一", "二", "三", "四", "五", "六", "日"]
+            .iter()
+            .enumerate()
+        {
+            range.set_value((row as u32, 0), Data::String(label.to_string()));
+            range.set_value((row as u32, 1), Data::Int(row as i64 + 1));
+        }
+        let rows: Vec<u32> = (0..=6).collect();
+        let features = shape_features(&range, &rows, (0, 0, 6, 1), None, NumberLocale::Auto);
+        assert_eq!(features.weekday_cells, 0);
+        assert!(features.calendar_ratio < 0.6);
+    }
+}
@@ -0,0 +1,119 @@
+//! WebAssembly 模块（`wasm` feature）
+//!
+//! 用 wasm-bindgen 导出给浏览器使用，上传前在页面中直接判断工作簿是数据表还是表单：
+//! - 工作簿以 `Uint8Array` 传入，只从内存读取，不涉及文件路径和文件系统
+//! - 配置以普通 JS 对象传入，字段与 JSON 配置文件相同，省略的字段使用默认值
+//! - 结果是字段名为 camelCase 的普通 JS 对象，并附带 TypeScript 类型声明
+//! - 失败时抛出 `Error`，其 `code`、`kind` 属性与 C FFI 的错误码一致
+//!
+//! wasm32 上不能创建线程，工作表总是串行分析，`threads` 配置不起作用。
+
+use crate::{
+    classify_excel_sheets_from_bytes, ClassifiedSheet, ClassifierConfig, LayoutError, LayoutType,
+    SheetType,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export type SheetType = "Data" | "Form" | "Unknown";
+
+export type LayoutType = "Crosstab" | "List" | "Lookup" | "Calendar" | "Notes";
+
+export interface SheetClassification {
+    sheetName: string;
+    visible: "Visible" | "Hidden" | "VeryHidden";
+    sheetType: SheetType;
+    layoutType: LayoutType | null;
+    confidence: number;
+    reason: string;
+    density: number;
+    columnNames: string[];
+}
+
+export interface LayoutViewError extends Error {
+    code: number;
+    kind: string;
+}
+"#;
+
+/// 单个工作表的分类结果（只保留页面提示需要的字段）
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SheetClassification {
+    sheet_name: String,
+    visible: String,
+    sheet_type: SheetType,
+    layout_type: Option<LayoutType>,
+    confidence: f64,
+    reason: String,
+    density: f64,
+    column_names: Vec<String>,
+}
+
+impl From<ClassifiedSheet> for SheetClassification {
+    fn from(sheet: ClassifiedSheet) -> Self {
+        SheetClassification {
+            sheet_name: sheet.sheet_name,
+            visible: sheet.visible,
+            sheet_type: sheet.sheet_type,
+            layout_type: sheet.layout_type,
+            confidence: sheet.confidence,
+            reason: sheet.classification_reason,
+            density: sheet.density,
+            column_names: sheet.column_names,
+        }
+    }
+}
+
+/// 对 `Uint8Array` 中的工作簿进行分类，返回每个工作表的分类结果
+/// `options` 为分类配置对象，可省略
+#[wasm_bindgen(js_name = classifyWorkbook, unchecked_return_type = "SheetClassification[]")]
+pub fn classify_workbook(data: &[u8], options: Option<js_sys::Object>) -> Result<JsValue, JsValue> {
+    let config: ClassifierConfig = match options {
+        Some(options) => serde_wasm_bindgen::from_value(options.into())
+            .map_err(|e| to_js_error(LayoutError::Config(e.to_string())))?,
+        None => ClassifierConfig::default(),
+    };
+    config.validate().map_err(to_js_error)?;
+    let sheets = classify_excel_sheets_from_bytes(data, &config).map_err(to_js_error)?;
+    let results: Vec<SheetClassification> = sheets.into_iter().map(Into::into).collect();
+    results
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+}
+
+/// 库版本号，例如 "0.1.0"
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/// 把 `LayoutError` 转换为带 `code`、`kind` 属性的 JS `Error`
+fn to_js_error(e: LayoutError) -> JsValue {
+    let error = js_sys::Error::new(&e.to_string());
+    error.set_name("LayoutViewError");
+    let _ = js_sys::Reflect::set(&error, &"code".into(), &e.code().into());
+    let _ = js_sys::Reflect::set(&error, &"kind".into(), &e.kind().into());
+    error.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheet_classification_fields() {
+        let bytes = std::fs::read("tests/test1_form.xlsx").unwrap();
+        let sheets =
+            classify_excel_sheets_from_bytes(&bytes, &ClassifierConfig::default()).unwrap();
+        let result = SheetClassification::from(sheets.into_iter().next().unwrap());
+        let json = serde_json::to_value(&result).unwrap();
+
+        assert_eq!(json["sheetType"], "Form");
+        assert!(json["sheetName"].is_string());
+        assert!(json["columnNames"].is_array());
+        assert!(json.get("sheet_type").is_none());
+    }
+}
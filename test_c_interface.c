@@ -1,43 +1,33 @@
+// 编译（layout_view.h 在仓库根目录）：
+//   cargo build --release
+//   cc test_c_interface.c -I . -L target/release -llayout_view -o test_c_interface
+//   LD_LIBRARY_PATH=target/release ./test_c_interface tests/test1_data.xlsx
 #include <stdio.h>
 #include <stdlib.h>
-#include <dlfcn.h>
-#include <string.h>
 
-int main() {
-    // Load the dynamic library
-    void* handle = dlopen("./target/release/liblayout_view.so", RTLD_LAZY);
-    if (!handle) {
-        fprintf(stderr, "Error: %s\n", dlerror());
-        return 1;
-    }
-
-    // Define function types
-    typedef char* (*classify_func_t)(const char*);
-    typedef void (*free_func_t)(char*);
+#include "layout_view.h"
 
-    // Get the function from the library
-    classify_func_t classify_excel_sheets_c = (classify_func_t) dlsym(handle, "classify_excel_sheets_c");
-    free_func_t free_c_string = (free_func_t) dlsym(handle, "free_c_string");
-
-    const char* error;
-    if ((error = dlerror()) != NULL) {
-        fprintf(stderr, "Error: %s\n", error);
+int main(int argc, char** argv) {
+    // 头文件与实际加载的动态库 ABI 版本不一致时拒绝调用
+    if (layout_view_abi_version() != LAYOUT_VIEW_ABI_VERSION) {
+        fprintf(stderr, "Error: liblayout_view ABI version %u, expected %u\n",
+                layout_view_abi_version(), LAYOUT_VIEW_ABI_VERSION);
         return 1;
     }
+    printf("layout-view %s (ABI %u)\n", layout_view_version(), layout_view_abi_version());
 
-    // Call the function
-    const char* file_path = "./files/test_data.xlsx";
+    const char* file_path = argc > 1 ? argv[1] : "./tests/test1_data.xlsx";
     printf("Classifying file: %s\n", file_path);
-    
+
     char* result = classify_excel_sheets_c(file_path);
-    if (result != NULL) {
-        printf("Result: %s\n", result);
-        free_c_string(result);
-    } else {
-        printf("Function returned NULL\n");
+    if (result == NULL) {
+        char* message = last_error_message_c();
+        fprintf(stderr, "Error %d: %s\n", last_error_code_c(), message);
+        free_c_string(message);
+        return 1;
     }
 
-    // Close the library
-    dlclose(handle);
+    printf("Result: %s\n", result);
+    free_c_string(result);
     return 0;
-}
\ No newline at end of file
+}
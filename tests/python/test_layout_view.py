@@ -0,0 +1,88 @@
+"""Python 扩展模块的冒烟测试：maturin develop && pytest tests/python"""
+
+import pathlib
+import threading
+import time
+
+import pytest
+
+import layout_view
+
+FIXTURES = pathlib.Path(__file__).resolve().parent.parent
+
+
+def test_path_and_bytes_input():
+    path = FIXTURES / "test1_data.xlsx"
+    from_str = layout_view.classify_excel_sheets(str(path))
+    from_pathlike = layout_view.classify_excel_sheets(path)
+    from_bytes = layout_view.classify_excel_sheets(path.read_bytes())
+    from_bytearray = layout_view.classify_excel_sheets(bytearray(path.read_bytes()))
+    expected = [sheet.to_dict() for sheet in from_str]
+    for sheets in (from_pathlike, from_bytes, from_bytearray):
+        assert [sheet.to_dict() for sheet in sheets] == expected
+
+
+def test_class_getters():
+    sheet = layout_view.classify_excel_sheets(FIXTURES / "test1_form.xlsx")[0]
+    assert isinstance(sheet, layout_view.ClassifiedSheet)
+    assert sheet.sheet_type == "Form"
+    assert sheet.layout_type is None or isinstance(sheet.layout_type, str)
+    assert 0.0 <= sheet.density <= 1.0
+    assert 0.0 <= sheet.cell_density <= 1.0
+    assert isinstance(sheet.explanation, dict)
+    assert sheet.to_dict()["sheet_name"] == sheet.sheet_name
+    assert "sheet_type='Form'" in repr(sheet)
+
+    column = sheet.column_data_types[0]
+    assert isinstance(column, layout_view.ColumnDataTypeInfo)
+    assert column.total_count >= column.numeric_count
+
+    density = layout_view.calculate_sheet_density(FIXTURES / "test1_data.xlsx")[0]
+    assert isinstance(density, layout_view.SheetDensity)
+    assert density.total_cells > 0
+
+
+def test_extract_records_is_iterator():
+    path = FIXTURES / "test1_data.xlsx"
+    records = layout_view.extract_records(path)
+    assert isinstance(records, layout_view.RecordIterator)
+    first = next(records)
+    assert set(first) == {"sheet_name", "row", "record"}
+    rest = list(records)
+    assert len(rest) + 1 == len(list(layout_view.extract_records(path.read_bytes())))
+    # 迭代结束后继续取值仍然是 StopIteration
+    with pytest.raises(StopIteration):
+        next(records)
+
+
+def test_errors():
+    with pytest.raises(FileNotFoundError):
+        layout_view.classify_excel_sheets(FIXTURES / "does_not_exist.xlsx")
+    with pytest.raises(layout_view.LayoutViewError):
+        layout_view.classify_excel_sheets(b"not a workbook")
+    with pytest.raises(ValueError):
+        layout_view.classify_excel_sheets(FIXTURES / "test1_data.xlsx", config={"hgih_density": 0.8})
+    # 记录迭代器在迭代时才打开工作簿
+    with pytest.raises(FileNotFoundError):
+        list(layout_view.extract_records(FIXTURES / "does_not_exist.xlsx"))
+
+
+def test_releases_gil():
+    # 分析期间释放 GIL 时，主线程可以在某次调用进行中继续执行；持有 GIL 时调用期间不可能有主线程的计时
+    data = (FIXTURES / "test3_form.xlsx").read_bytes()
+    calls = []
+    ticks = []
+
+    def worker():
+        for _ in range(10):
+            start = time.perf_counter()
+            layout_view.classify_excel_sheets(data)
+            calls.append((start, time.perf_counter()))
+
+    thread = threading.Thread(target=worker)
+    thread.start()
+    while thread.is_alive():
+        ticks.append(time.perf_counter())
+    thread.join()
+
+    assert any(start < tick < end for start, end in calls for tick in ticks)